@@ -0,0 +1,508 @@
+use crate::error::XbelError;
+use crate::remote::RemoteHref;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use itertools::Itertools;
+use percent_encoding::percent_decode;
+use quick_xml::Reader;
+use quick_xml::escape::resolve_predefined_entity;
+use quick_xml::events::attributes::{Attribute, Attributes};
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::name::QName;
+use serde::Deserialize;
+use std::borrow::Cow;
+use std::error::Error;
+use std::ffi::OsStr;
+use std::fmt;
+use std::io::BufRead;
+use std::os::unix::ffi::OsStrExt;
+use std::path::PathBuf;
+use std::str::{self, FromStr};
+
+fn href_attribute(attributes: Attributes) -> Option<Cow<[u8]>> {
+    attributes
+        .filter_map(|a| match a {
+            Ok(Attribute {
+                key: QName(b"href"),
+                value,
+            }) => Some(value),
+            _ => None,
+        })
+        .exactly_one()
+        .ok()
+}
+
+/// Parse a timestamp as written by GLib (RFC 3339, usually in UTC with optional fractional seconds).
+/// Malformed timestamps (such as a five-digit year) yield `None` rather than an error,
+/// so that a single bad value does not prevent cleaning the rest of the file.
+fn parse_timestamp(value: &[u8]) -> Option<DateTime<Utc>> {
+    let value = str::from_utf8(value).ok()?;
+    DateTime::parse_from_rfc3339(value)
+        .map(|timestamp| timestamp.with_timezone(&Utc))
+        .or_else(|_| {
+            NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S%.f")
+                .map(|timestamp| timestamp.and_utc())
+        })
+        .ok()
+}
+
+/// One of the timestamps recorded for each bookmark.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Timestamp {
+    Added,
+    Modified,
+    #[default]
+    Visited,
+}
+
+#[derive(Debug)]
+pub struct UnknownTimestampError {
+    timestamp: String,
+}
+impl fmt::Display for UnknownTimestampError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "UnknownTimestampError: {} (expected added, modified or visited)",
+            self.timestamp
+        )
+    }
+}
+impl Error for UnknownTimestampError {}
+
+impl fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Timestamp::Added => "added",
+            Timestamp::Modified => "modified",
+            Timestamp::Visited => "visited",
+        })
+    }
+}
+
+impl FromStr for Timestamp {
+    type Err = UnknownTimestampError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "added" => Ok(Timestamp::Added),
+            "modified" => Ok(Timestamp::Modified),
+            "visited" => Ok(Timestamp::Visited),
+            _ => Err(UnknownTimestampError {
+                timestamp: s.to_string(),
+            }),
+        }
+    }
+}
+
+/// An application that registered a bookmark, from a `<bookmark:application>` element.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Application {
+    pub name: String,
+    /// How often the application opened the file (GLib assumes 1 if the attribute is missing).
+    pub count: u64,
+}
+
+fn is_application(e: &BytesStart) -> bool {
+    e.name() == QName(b"bookmark:application")
+}
+
+fn application_name(e: &BytesStart) -> Result<Option<String>, quick_xml::Error> {
+    Ok(match e.try_get_attribute("name")? {
+        Some(name) => Some(name.unescape_value()?.into_owned()),
+        None => None,
+    })
+}
+
+/// A `<bookmark>` element, along with the parts of it that rules can match on.
+#[derive(Debug, Clone, Default)]
+pub struct Bookmark {
+    /// The href, percent-decoded (lossily, since it may not be valid UTF-8).
+    pub href: String,
+    /// The href as written in the file, i.e. percent-encoded.
+    pub encoded_href: String,
+    pub added: Option<DateTime<Utc>>,
+    pub modified: Option<DateTime<Utc>>,
+    pub visited: Option<DateTime<Utc>>,
+    /// The MIME type from the `<mime:mime-type>` metadata, if any.
+    pub mime_type: Option<String>,
+    /// The applications from the `<bookmark:applications>` metadata.
+    pub applications: Vec<Application>,
+    /// The groups from the `<bookmark:groups>` metadata.
+    pub groups: Vec<String>,
+    /// All the events making up the element, so that it can be written out again unchanged.
+    pub events: Vec<Event<'static>>,
+}
+
+impl Bookmark {
+    /// The bookmark with the given attributes, or `None` if they do not include exactly one href.
+    pub fn from_attributes(attributes: Attributes) -> Option<Self> {
+        let href = href_attribute(attributes.clone())?;
+        let mut bookmark = Bookmark {
+            href: percent_decode(&href).decode_utf8_lossy().into_owned(),
+            encoded_href: String::from_utf8_lossy(&href).into_owned(),
+            ..Default::default()
+        };
+        for attribute in attributes.flatten() {
+            match attribute.key {
+                QName(b"added") => bookmark.added = parse_timestamp(&attribute.value),
+                QName(b"modified") => bookmark.modified = parse_timestamp(&attribute.value),
+                QName(b"visited") => bookmark.visited = parse_timestamp(&attribute.value),
+                _ => (),
+            }
+        }
+        Some(bookmark)
+    }
+
+    /// Read the rest of a `<bookmark>` element whose start tag (at the given position) has just been read.
+    pub fn read<R: BufRead>(
+        reader: &mut Reader<R>,
+        start: BytesStart<'static>,
+        position: u64,
+    ) -> Result<Self, XbelError> {
+        let mut bookmark = Bookmark::from_attributes(start.attributes())
+            .ok_or(XbelError::BookmarkWithoutSingleHref { position })?;
+        let mut events = vec![Event::Start(start)];
+        let mut buf = Vec::new();
+        let mut group: Option<String> = None;
+        loop {
+            let event = match reader.read_event_into(&mut buf) {
+                Ok(event) => event.into_owned(),
+                Err(error) => {
+                    return Err(XbelError::Xml {
+                        position: reader.error_position(),
+                        error,
+                    });
+                }
+            };
+            buf.clear();
+            bookmark
+                .read_metadata(&event, &mut group)
+                .map_err(|error| XbelError::Xml {
+                    position: reader.buffer_position(),
+                    error,
+                })?;
+            match &event {
+                Event::End(e) if e.name() == QName(b"bookmark") => {
+                    events.push(event);
+                    bookmark.events = events;
+                    return Ok(bookmark);
+                }
+                Event::Eof => {
+                    return Err(XbelError::UnterminatedBookmark {
+                        position,
+                        href: bookmark.href,
+                    });
+                }
+                _ => (),
+            }
+            events.push(event);
+        }
+    }
+
+    /// Take the metadata that rules can match on from an event inside the bookmark.
+    /// `group` holds the text of the `<bookmark:group>` element being read, if any.
+    fn read_metadata(
+        &mut self,
+        event: &Event,
+        group: &mut Option<String>,
+    ) -> Result<(), quick_xml::Error> {
+        match event {
+            Event::Start(e) | Event::Empty(e) if e.name() == QName(b"mime:mime-type") => {
+                if let Some(mime_type) = e.try_get_attribute("type")? {
+                    self.mime_type = Some(mime_type.unescape_value()?.into_owned());
+                }
+            }
+            Event::Start(e) | Event::Empty(e) if is_application(e) => {
+                if let Some(name) = application_name(e)? {
+                    let count = match e.try_get_attribute("count")? {
+                        Some(count) => count.unescape_value()?.parse().unwrap_or(1),
+                        None => 1,
+                    };
+                    self.applications.push(Application { name, count });
+                }
+            }
+            Event::Start(e) if e.name() == QName(b"bookmark:group") => {
+                *group = Some(String::new());
+            }
+            Event::Text(e) => {
+                if let Some(group) = group {
+                    group.push_str(&e.decode()?);
+                }
+            }
+            Event::CData(e) => {
+                if let Some(group) = group {
+                    group.push_str(&e.decode()?);
+                }
+            }
+            Event::GeneralRef(e) => {
+                if let Some(group) = group {
+                    match e.resolve_char_ref()? {
+                        Some(c) => group.push(c),
+                        None => {
+                            let name = e.decode()?;
+                            match resolve_predefined_entity(&name) {
+                                Some(resolved) => group.push_str(resolved),
+                                None => group.push_str(&format!("&{name};")),
+                            }
+                        }
+                    }
+                }
+            }
+            Event::End(e) if e.name() == QName(b"bookmark:group") => {
+                self.groups.extend(group.take());
+            }
+            _ => (),
+        }
+        Ok(())
+    }
+
+    /// Remove the `<bookmark:application>` elements for the named application
+    /// (along with the whitespace preceding them), if there are any.
+    pub fn remove_application(&mut self, name: &str) -> Result<(), quick_xml::Error> {
+        self.applications
+            .retain(|application| application.name != name);
+        while let Some(start) = self.events.iter().position(|event| match event {
+            Event::Start(e) | Event::Empty(e) if is_application(e) => {
+                application_name(e).is_ok_and(|n| n.as_deref() == Some(name))
+            }
+            _ => false,
+        }) {
+            let end = match &self.events[start] {
+                Event::Start(_) => self.events[start..]
+                    .iter()
+                    .position(|event| matches!(event, Event::End(e) if e.name() == QName(b"bookmark:application")))
+                    .map_or(start, |length| start + length),
+                _ => start,
+            };
+            let start = match &self.events[start - 1] {
+                Event::Text(e) if e.decode()?.chars().all(char::is_whitespace) => start - 1,
+                _ => start,
+            };
+            self.events.drain(start..=end);
+        }
+        Ok(())
+    }
+
+    /// The local path, if the href is a `file://` URI.
+    pub fn path(&self) -> Option<&str> {
+        self.href.strip_prefix("file://")
+    }
+
+    /// The local path decoded to raw bytes, if the href is a `file://` URI.
+    /// Unlike `path`, this names the actual file even if its name is not valid UTF-8.
+    pub fn local_path(&self) -> Option<PathBuf> {
+        let path = self.encoded_href.strip_prefix("file://")?;
+        let bytes: Vec<u8> = percent_decode(path.as_bytes()).collect();
+        Some(PathBuf::from(OsStr::from_bytes(&bytes)))
+    }
+
+    /// The scheme, user, host, port and path, if the href is a remote location such as `sftp://me@example.com/`.
+    pub fn remote(&self) -> Option<RemoteHref> {
+        RemoteHref::parse(&self.encoded_href)
+    }
+
+    pub fn timestamp(&self, timestamp: Timestamp) -> Option<DateTime<Utc>> {
+        match timestamp {
+            Timestamp::Added => self.added,
+            Timestamp::Modified => self.modified,
+            Timestamp::Visited => self.visited,
+        }
+    }
+}
+
+/// Read all the bookmarks of an XBEL document, skipping everything else.
+pub fn read_all<R: BufRead>(reader: R) -> Result<Vec<Bookmark>, XbelError> {
+    let mut reader = Reader::from_reader(reader);
+    let mut buf = Vec::new();
+    let mut bookmarks = Vec::new();
+    loop {
+        let position = reader.buffer_position();
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(e)) if e.name() == QName(b"bookmark") => {
+                let start = e.into_owned();
+                bookmarks.push(Bookmark::read(&mut reader, start, position)?);
+            }
+            Ok(Event::Eof) => return Ok(bookmarks),
+            Ok(_) => (),
+            Err(error) => {
+                return Err(XbelError::Xml {
+                    position: reader.error_position(),
+                    error,
+                });
+            }
+        }
+        buf.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bookmark(start: &str) -> Bookmark {
+        Bookmark::from_attributes(BytesStart::from_content(start, 8).attributes()).unwrap()
+    }
+
+    #[test]
+    fn timestamps() {
+        let bookmark = bookmark(
+            r#"bookmark href="cdda://sr0/" added="2025-09-13T11:59:30.774505Z" modified="2025-09-13T11:59:30Z" visited="22022-04-08T20:00:00Z""#,
+        );
+        assert_eq!(
+            Some("2025-09-13T11:59:30.774505Z".parse().unwrap()),
+            bookmark.added
+        );
+        assert_eq!(
+            Some("2025-09-13T11:59:30Z".parse().unwrap()),
+            bookmark.modified
+        );
+        assert_eq!(None, bookmark.visited);
+    }
+
+    #[test]
+    fn decoded_path() {
+        let bookmark = bookmark(r#"bookmark href="file:///opt/A%20Directory/A-File.txt%BC""#);
+        assert_eq!(Some("/opt/A Directory/A-File.txt\u{FFFD}"), bookmark.path());
+        assert_eq!(None, bookmark.added);
+    }
+
+    #[test]
+    fn read_metadata() {
+        let input = r#"<bookmark href="file:///home/me/A-File.txt" added="2020-09-24T20:00:00Z" modified="2020-09-25T20:00:00Z" visited="2020-09-25T20:00:00Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="text/plain"/>
+        <bookmark:groups>
+          <bookmark:group>gedit</bookmark:group>
+          <bookmark:group>Text &amp; Code &#x1F4DD;</bookmark:group>
+        </bookmark:groups>
+        <bookmark:applications>
+          <bookmark:application name="gedit" exec="&apos;gedit %u&apos;" modified="2020-09-25T20:00:00Z" count="1234"/>
+          <bookmark:application name="org.gnome.TextEditor" exec="&apos;gnome-text-editor %U&apos;" modified="2020-09-25T20:00:00Z"/>
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>
+  <bookmark href="file:///home/me/Another-File.txt">"#;
+        let mut reader = Reader::from_str(input);
+        let Event::Start(start) = reader.read_event().unwrap() else {
+            panic!("no start tag");
+        };
+        let bookmark = Bookmark::read(&mut reader, start.into_owned(), 0).unwrap();
+        assert_eq!(Some("text/plain"), bookmark.mime_type.as_deref());
+        assert_eq!(
+            vec![
+                Application {
+                    name: String::from("gedit"),
+                    count: 1234
+                },
+                Application {
+                    name: String::from("org.gnome.TextEditor"),
+                    count: 1
+                },
+            ],
+            bookmark.applications
+        );
+        assert_eq!(
+            vec![String::from("gedit"), String::from("Text & Code \u{1F4DD}")],
+            bookmark.groups
+        );
+        assert_eq!(36, bookmark.events.len());
+        assert!(
+            matches!(bookmark.events.last(), Some(Event::End(e)) if e.name() == QName(b"bookmark"))
+        );
+    }
+
+    #[test]
+    fn read_other_events() {
+        let input = r#"<bookmark href="file:///home/me/A-File.txt">
+    <!-- a comment -->
+    <info>
+      <?processing instruction?>
+      <metadata owner="http://freedesktop.org">
+        <bookmark:groups>
+          <bookmark:group><![CDATA[Text & <Code>]]></bookmark:group>
+        </bookmark:groups>
+      </metadata>
+    </info>
+  </bookmark>"#;
+        let mut reader = Reader::from_str(input);
+        let Event::Start(start) = reader.read_event().unwrap() else {
+            panic!("no start tag");
+        };
+        let bookmark = Bookmark::read(&mut reader, start.into_owned(), 0).unwrap();
+        assert_eq!(vec![String::from("Text & <Code>")], bookmark.groups);
+        assert!(
+            bookmark
+                .events
+                .iter()
+                .any(|event| matches!(event, Event::Comment(_)))
+        );
+        assert!(
+            bookmark
+                .events
+                .iter()
+                .any(|event| matches!(event, Event::PI(_)))
+        );
+    }
+
+    #[test]
+    fn read_unterminated() {
+        let mut reader = Reader::from_str(r#"<bookmark href="file:///home/me/A-File.txt"><info>"#);
+        let Event::Start(start) = reader.read_event().unwrap() else {
+            panic!("no start tag");
+        };
+        assert!(matches!(
+            Bookmark::read(&mut reader, start.into_owned(), 0),
+            Err(XbelError::UnterminatedBookmark { position: 0, href }) if href == "file:///home/me/A-File.txt"
+        ));
+    }
+
+    #[test]
+    fn without_href() {
+        assert!(
+            Bookmark::from_attributes(
+                BytesStart::from_content(r#"bookmark added="2025-09-13T11:59:30Z""#, 8)
+                    .attributes()
+            )
+            .is_none()
+        );
+        assert!(matches!(
+            read_all(
+                &b"<xbel>\n  <bookmark added=\"2025-09-13T11:59:30Z\"></bookmark>\n</xbel>"[..]
+            ),
+            Err(XbelError::BookmarkWithoutSingleHref { position: 9 })
+        ));
+    }
+
+    #[test]
+    fn read_all_bookmarks() {
+        let input = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xbel version="1.0"
+      xmlns:bookmark="http://www.freedesktop.org/standards/desktop-bookmarks"
+      xmlns:mime="http://www.freedesktop.org/standards/shared-mime-info"
+>
+  <bookmark href="file:///home/me/A-File.txt" added="2020-09-24T20:00:00Z" modified="2020-09-25T20:00:00Z" visited="2020-09-25T20:00:00Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="text/plain"/>
+      </metadata>
+    </info>
+  </bookmark>
+  <bookmark href="trash:///A-File.txt" added="2020-09-24T20:00:00Z" modified="2020-09-25T20:00:00Z" visited="2020-09-25T20:00:00Z">
+  </bookmark>
+</xbel>
+"#;
+        let bookmarks = read_all(input.as_bytes()).unwrap();
+        assert_eq!(
+            vec!["file:///home/me/A-File.txt", "trash:///A-File.txt"],
+            bookmarks
+                .iter()
+                .map(|bookmark| bookmark.href.as_str())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(Some("text/plain"), bookmarks[0].mime_type.as_deref());
+        assert!(read_all(&b"<xbel><bookmark href=\"file:///tmp/a\">"[..]).is_err());
+    }
+}
@@ -0,0 +1,341 @@
+use crate::bookmark::Timestamp;
+use crate::config::Settings;
+use crate::list::ListFormat;
+use crate::report::ReportFormat;
+use crate::rules::Rule;
+use clap::{CommandFactory, Parser, Subcommand};
+use std::ffi::OsString;
+use std::path::PathBuf;
+
+/// Clean the list of recently used files.
+#[derive(Debug, Parser)]
+#[command(version, about)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Remove entries from the list of recently used files.
+    ///
+    /// This is the default command: `clean-recently-used /tmp` is the same as `clean-recently-used clean /tmp`.
+    Clean(CleanArgs),
+    /// Undo the last clean with --backup, restoring the list from the backup it made.
+    Restore(InputArgs),
+    /// Print the entries of the list of recently used files.
+    List(ListArgs),
+    /// Print statistics about the list of recently used files,
+    /// such as the applications, directories and MIME types with the most entries.
+    Stats(InputArgs),
+    /// Clean a list read from standard input, writing the result to standard output.
+    ///
+    /// This takes the same rules and options as the clean command,
+    /// but does not read the configuration file or change any other files.
+    Filter(Settings),
+}
+
+#[derive(Debug, clap::Args)]
+pub struct InputArgs {
+    /// Read the list from this file (- for standard input)
+    /// instead of ~/.local/share/recently-used.xbel.
+    #[arg(long, short = 'i', value_name = "FILE")]
+    pub input: Option<PathBuf>,
+}
+
+#[derive(Debug, clap::Args)]
+pub struct CleanArgs {
+    #[command(flatten)]
+    pub settings: Settings,
+    #[command(flatten)]
+    pub input: InputArgs,
+    /// Write the cleaned list to this file (- for standard output) instead of replacing the input.
+    /// The input is only replaced (keeping a backup with --backup) if it is the same file as the output,
+    /// which is the default unless the input is standard input.
+    #[arg(long, short = 'o', value_name = "FILE")]
+    pub output: Option<PathBuf>,
+    /// Read the configuration from this file
+    /// instead of ~/.config/clean-recently-used/config.toml.
+    #[arg(long, value_name = "FILE")]
+    pub config: Option<PathBuf>,
+    /// Also apply the settings of this profile from the configuration file.
+    #[arg(long, value_name = "NAME")]
+    pub profile: Option<String>,
+    /// Keep the previous version of the list as a .bak file next to it, for the restore command.
+    /// Note that the backup still contains all the entries that were removed.
+    #[arg(long)]
+    pub backup: bool,
+    /// Only print which entries would be removed, and why, without changing anything.
+    #[arg(long, short = 'n')]
+    pub dry_run: bool,
+    /// Print a unified diff between the list and the cleaned list.
+    /// In a dry run, the diff replaces the listing of the entries that would be removed.
+    /// Not possible if the cleaned list or the report is written to standard output.
+    #[arg(long)]
+    pub diff: bool,
+    /// Write a machine-readable report of the removed entries to this file (- for standard output,
+    /// which is not possible if the cleaned list is written there too).
+    #[arg(long, value_name = "FILE")]
+    pub report: Option<PathBuf>,
+    /// The format of the --report.
+    #[arg(long, value_name = "FORMAT", value_enum, default_value_t)]
+    pub report_format: ReportFormat,
+}
+
+#[derive(Debug, clap::Args)]
+pub struct ListArgs {
+    #[command(flatten)]
+    pub input: InputArgs,
+    /// Only list the entries that these rules would remove
+    /// (the same rules as for the clean command; without rules, all entries are listed).
+    #[arg(value_name = "RULE")]
+    pub rules: Vec<Rule>,
+    /// Sort the entries by this timestamp (added, modified or visited), newest first,
+    /// instead of listing them in the order of the file.
+    #[arg(long, value_name = "TIMESTAMP")]
+    pub sort: Option<Timestamp>,
+    /// How to print the entries.
+    #[arg(long, value_name = "FORMAT", value_enum, default_value_t)]
+    pub format: ListFormat,
+}
+
+/// Insert the default `clean` command into the command line if no command was given,
+/// so that `clean-recently-used /tmp /var/tmp` keeps working as it did before there were commands,
+/// and a bare `clean-recently-used` still cleans with the rules from the configuration file.
+pub fn with_default_command(args: impl IntoIterator<Item = OsString>) -> Vec<OsString> {
+    let mut args: Vec<OsString> = args.into_iter().collect();
+    let command = Cli::command();
+    let is_command_or_global_option = args.get(1).is_some_and(|first| {
+        command
+            .get_subcommands()
+            .map(|subcommand| subcommand.get_name())
+            .chain(["help", "-h", "--help", "-V", "--version"])
+            .any(|name| first == name)
+    });
+    if !args.is_empty() && !is_command_or_global_option {
+        args.insert(1, OsString::from("clean"));
+    }
+    args
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::UnknownSchemes;
+
+    fn try_parse(args: &[&str]) -> Result<Cli, clap::Error> {
+        Cli::try_parse_from(with_default_command(args.iter().map(OsString::from)))
+    }
+
+    fn parse(args: &[&str]) -> Cli {
+        try_parse(args).unwrap()
+    }
+
+    #[test]
+    fn verify() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn clean() {
+        let Command::Clean(args) = parse(&[
+            "clean-recently-used",
+            "clean",
+            "/tmp",
+            "--prune-app",
+            "vlc",
+            "glob:*.kdbx",
+            "--prune-app=gedit",
+            "--max-entries=10",
+            "--max-entries-by",
+            "modified",
+            "--gtk-max-age",
+            "--profile",
+            "work",
+        ])
+        .command
+        else {
+            panic!("not the clean command");
+        };
+        assert_eq!(2, args.settings.rules.len());
+        assert_eq!(
+            vec![String::from("vlc"), String::from("gedit")],
+            args.settings.prune_apps
+        );
+        assert_eq!(Some(10), args.settings.max_entries);
+        assert_eq!(Some(Timestamp::Modified), args.settings.max_entries_by);
+        assert!(args.settings.gtk_max_age);
+        assert_eq!(None, args.config);
+        assert_eq!(Some("work"), args.profile.as_deref());
+        assert!(!args.backup);
+        assert_eq!(None, args.input.input);
+        assert_eq!(None, args.output);
+    }
+
+    #[test]
+    fn input_output() {
+        let Command::Clean(args) = parse(&[
+            "clean-recently-used",
+            "-i",
+            "-",
+            "--output=out.xbel",
+            "/tmp",
+        ])
+        .command
+        else {
+            panic!("not the clean command");
+        };
+        assert_eq!(Some(PathBuf::from("-")), args.input.input);
+        assert_eq!(Some(PathBuf::from("out.xbel")), args.output);
+        let Command::List(args) =
+            parse(&["clean-recently-used", "list", "--input", "in.xbel"]).command
+        else {
+            panic!("not the list command");
+        };
+        assert_eq!(Some(PathBuf::from("in.xbel")), args.input.input);
+        let Command::Stats(args) =
+            parse(&["clean-recently-used", "stats", "-i", "in.xbel"]).command
+        else {
+            panic!("not the stats command");
+        };
+        assert_eq!(Some(PathBuf::from("in.xbel")), args.input);
+        assert!(try_parse(&["clean-recently-used", "stats", "-o", "out.xbel"]).is_err());
+        let Command::Restore(args) =
+            parse(&["clean-recently-used", "restore", "-i", "in.xbel"]).command
+        else {
+            panic!("not the restore command");
+        };
+        assert_eq!(Some(PathBuf::from("in.xbel")), args.input);
+    }
+
+    #[test]
+    fn default_command() {
+        let Command::Clean(args) = parse(&["clean-recently-used", "/tmp", "/var/tmp"]).command
+        else {
+            panic!("not the clean command");
+        };
+        assert_eq!(2, args.settings.rules.len());
+        let Command::Clean(args) = parse(&["clean-recently-used"]).command else {
+            panic!("not the clean command");
+        };
+        assert!(args.settings.rules.is_empty());
+        let Command::Clean(args) = parse(&["clean-recently-used", "--backup", "/tmp"]).command
+        else {
+            panic!("not the clean command");
+        };
+        assert!(args.backup);
+        assert!(!args.dry_run);
+        let Command::Clean(args) = parse(&["clean-recently-used", "-n", "/tmp"]).command else {
+            panic!("not the clean command");
+        };
+        assert!(args.dry_run);
+        assert!(!args.diff);
+        assert_eq!(None, args.report);
+        assert_eq!(ReportFormat::Json, args.report_format);
+        let Command::Clean(args) = parse(&[
+            "clean-recently-used",
+            "--report=-",
+            "--report-format",
+            "json-lines",
+            "/tmp",
+        ])
+        .command
+        else {
+            panic!("not the clean command");
+        };
+        assert_eq!(Some(PathBuf::from("-")), args.report);
+        assert_eq!(ReportFormat::JsonLines, args.report_format);
+        let Command::Clean(args) = parse(&["clean-recently-used", "--diff", "-n", "/tmp"]).command
+        else {
+            panic!("not the clean command");
+        };
+        assert!(args.diff);
+        assert!(args.dry_run);
+        assert!(matches!(
+            parse(&["clean-recently-used", "restore"]).command,
+            Command::Restore(InputArgs { input: None })
+        ));
+        assert!(matches!(
+            parse(&["clean-recently-used", "stats"]).command,
+            Command::Stats(InputArgs { input: None })
+        ));
+    }
+
+    #[test]
+    fn list() {
+        let Command::List(args) = parse(&["clean-recently-used", "list"]).command else {
+            panic!("not the list command");
+        };
+        assert!(args.rules.is_empty());
+        assert_eq!(None, args.sort);
+        assert_eq!(ListFormat::Table, args.format);
+        let Command::List(args) = parse(&[
+            "clean-recently-used",
+            "list",
+            "--sort=visited",
+            "--format",
+            "json",
+            "/tmp",
+            "keep:/tmp/keep",
+        ])
+        .command
+        else {
+            panic!("not the list command");
+        };
+        assert_eq!(2, args.rules.len());
+        assert_eq!(Some(Timestamp::Visited), args.sort);
+        assert_eq!(ListFormat::Json, args.format);
+        assert!(try_parse(&["clean-recently-used", "list", "--sort=opened"]).is_err());
+    }
+
+    #[test]
+    fn filter() {
+        let Command::Filter(settings) = parse(&[
+            "clean-recently-used",
+            "filter",
+            "/tmp",
+            "--max-entries",
+            "10",
+        ])
+        .command
+        else {
+            panic!("not the filter command");
+        };
+        assert_eq!(1, settings.rules.len());
+        assert_eq!(Some(10), settings.max_entries);
+        assert_eq!(None, settings.unknown_schemes);
+        let Command::Clean(args) = parse(&[
+            "clean-recently-used",
+            "--scheme=smb",
+            "--scheme",
+            "davs",
+            "--unknown-schemes",
+            "warn",
+        ])
+        .command
+        else {
+            panic!("not the clean command");
+        };
+        assert_eq!(
+            vec![String::from("smb"), String::from("davs")],
+            args.settings.schemes
+        );
+        assert_eq!(Some(UnknownSchemes::Warn), args.settings.unknown_schemes);
+        assert!(try_parse(&["clean-recently-used", "--unknown-schemes=ignore"]).is_err());
+        assert!(try_parse(&["clean-recently-used", "filter", "--dry-run"]).is_err());
+    }
+
+    #[test]
+    fn errors() {
+        for args in [
+            &["clean-recently-used", "--dry-run=yes"][..],
+            &["clean-recently-used", "--prune-app"],
+            &["clean-recently-used", "--max-entries=ten"],
+            &["clean-recently-used", "--max-entries-by=opened"],
+            &["clean-recently-used", "glob:/home/["],
+            &["clean-recently-used", "restore", "/tmp"],
+        ] {
+            assert!(try_parse(args).is_err(), "{args:?}");
+        }
+    }
+}
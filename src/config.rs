@@ -0,0 +1,302 @@
+use crate::bookmark::Timestamp;
+use crate::gtk;
+use crate::rules::{Filter, Rule, UnknownSchemes};
+use chrono::Local;
+use directories::BaseDirs;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug)]
+pub struct ConfigError {
+    path: PathBuf,
+    message: String,
+}
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "ConfigError: {}: {}", self.path.display(), self.message)
+    }
+}
+impl Error for ConfigError {}
+
+/// Settings determining how the list is filtered,
+/// from the configuration file, one of its profiles, or the command line.
+#[derive(Debug, Default, Deserialize, clap::Args)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Settings {
+    /// Rules selecting the entries to remove:
+    /// paths (removing everything below them), or raw:, glob:, regex:, mime:, app:, group:,
+    /// host:, user:, remote:,
+    /// added-before:, modified-before:, visited-before:, added-older-than:, modified-older-than:,
+    /// visited-older-than:, recent: or missing rules.
+    /// Prefix a rule with keep: to keep entries it matches;
+    /// the last rule matching an entry decides.
+    #[serde(default)]
+    #[arg(value_name = "RULE")]
+    pub rules: Vec<Rule>,
+    /// Remove this application from all entries,
+    /// removing entries that no other application refers to.
+    #[serde(default)]
+    #[arg(long = "prune-app", value_name = "APP")]
+    pub prune_apps: Vec<String>,
+    /// Keep at most this many entries, the most recently visited ones.
+    #[arg(long, value_name = "N")]
+    pub max_entries: Option<usize>,
+    /// Which timestamp --max-entries uses: added, modified or visited.
+    #[arg(long, value_name = "TIMESTAMP")]
+    pub max_entries_by: Option<Timestamp>,
+    /// Remove entries older than GTK’s gtk-recent-files-max-age setting.
+    #[serde(default)]
+    #[arg(long)]
+    pub gtk_max_age: bool,
+    /// Also accept entries with this scheme (e.g. google-drive),
+    /// besides file and the built-in local and GVfs network schemes (such as trash, sftp, smb or davs).
+    #[serde(default)]
+    #[arg(long = "scheme", value_name = "SCHEME")]
+    pub schemes: Vec<String>,
+    /// What to do with entries with any other scheme.
+    #[arg(long, value_name = "POLICY", value_enum)]
+    pub unknown_schemes: Option<UnknownSchemes>,
+}
+
+impl Settings {
+    /// Add other settings to these ones:
+    /// rules, applications and schemes are appended, other options are overridden if set.
+    pub fn extend(&mut self, other: Settings) {
+        self.rules.extend(other.rules);
+        self.prune_apps.extend(other.prune_apps);
+        self.max_entries = other.max_entries.or(self.max_entries);
+        self.max_entries_by = other.max_entries_by.or(self.max_entries_by);
+        self.gtk_max_age |= other.gtk_max_age;
+        self.schemes.extend(other.schemes);
+        self.unknown_schemes = other.unknown_schemes.or(self.unknown_schemes);
+    }
+
+    pub fn into_filter(self) -> Result<Filter, Box<dyn Error>> {
+        let mut rules = Vec::new();
+        // first, so that keep rules can still make exceptions to it
+        if self.gtk_max_age
+            && let Some(max_age) = gtk::recent_files_max_age()?
+        {
+            let cutoff = (Local::now() - max_age).to_utc();
+            rules.push(Rule::Before(Timestamp::Visited, cutoff));
+        }
+        rules.extend(self.rules);
+        Ok(Filter {
+            rules,
+            prune_applications: self.prune_apps,
+            max_entries: self.max_entries,
+            max_entries_by: self.max_entries_by.unwrap_or_default(),
+            schemes: self.schemes,
+            unknown_schemes: self.unknown_schemes.unwrap_or_default(),
+        })
+    }
+}
+
+/// The configuration file: settings that always apply,
+/// and named profiles whose settings only apply when selected.
+#[derive(Debug, Default)]
+pub struct Config {
+    pub settings: Settings,
+    pub profiles: BTreeMap<String, Settings>,
+}
+
+/// The configuration file as written, i.e. [`Settings`] with an additional `profiles` table.
+/// (`#[serde(flatten)]` cannot be combined with `deny_unknown_fields`.)
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+struct ConfigFile {
+    #[serde(default)]
+    rules: Vec<Rule>,
+    #[serde(default)]
+    prune_apps: Vec<String>,
+    max_entries: Option<usize>,
+    max_entries_by: Option<Timestamp>,
+    #[serde(default)]
+    gtk_max_age: bool,
+    #[serde(default)]
+    schemes: Vec<String>,
+    unknown_schemes: Option<UnknownSchemes>,
+    #[serde(default)]
+    profiles: BTreeMap<String, Settings>,
+}
+
+impl Config {
+    fn parse(contents: &str) -> Result<Self, toml::de::Error> {
+        let file: ConfigFile = toml::from_str(contents)?;
+        Ok(Config {
+            settings: Settings {
+                rules: file.rules,
+                prune_apps: file.prune_apps,
+                max_entries: file.max_entries,
+                max_entries_by: file.max_entries_by,
+                gtk_max_age: file.gtk_max_age,
+                schemes: file.schemes,
+                unknown_schemes: file.unknown_schemes,
+            },
+            profiles: file.profiles,
+        })
+    }
+}
+
+/// The default location of the configuration file,
+/// `clean-recently-used/config.toml` in the user’s configuration directory.
+fn default_path() -> Option<PathBuf> {
+    BaseDirs::new().map(|base_dirs| {
+        base_dirs
+            .config_dir()
+            .join("clean-recently-used")
+            .join("config.toml")
+    })
+}
+
+/// Load the settings from the configuration file,
+/// including those of the given profile if any.
+///
+/// Without an explicit path, a missing configuration file is the same as an empty one.
+pub fn load(path: Option<&Path>, profile: Option<&str>) -> Result<Settings, ConfigError> {
+    let (path, explicit) = match path {
+        Some(path) => (path.to_path_buf(), true),
+        None => match default_path() {
+            Some(path) => (path, false),
+            None => (PathBuf::from("config.toml"), false),
+        },
+    };
+    let config_error = |message: String| ConfigError {
+        path: path.clone(),
+        message,
+    };
+    let mut config = match fs::read_to_string(&path) {
+        Ok(contents) => Config::parse(&contents).map_err(|e| config_error(e.to_string()))?,
+        Err(e) if e.kind() == ErrorKind::NotFound && !explicit => Config::default(),
+        Err(e) => return Err(config_error(e.to_string())),
+    };
+    let mut settings = config.settings;
+    if let Some(profile) = profile {
+        let profile = config
+            .profiles
+            .remove(profile)
+            .ok_or_else(|| config_error(format!("no profile named {profile:?}")))?;
+        settings.extend(profile);
+    }
+    Ok(settings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::process;
+
+    #[test]
+    fn parse() {
+        let config = Config::parse(
+            r#"
+rules = ["/tmp", "/var/tmp", "glob:*.kdbx"]
+prune-apps = ["vlc"]
+max-entries-by = "modified"
+schemes = ["smb", "davs"]
+
+[profiles.work]
+rules = ["/home/me/work", "keep:/home/me/work/shared"]
+max-entries = 100
+gtk-max-age = true
+unknown-schemes = "warn"
+
+[profiles.empty]
+"#,
+        )
+        .unwrap();
+        assert_eq!(3, config.settings.rules.len());
+        assert_eq!(vec![String::from("vlc")], config.settings.prune_apps);
+        assert_eq!(None, config.settings.max_entries);
+        assert_eq!(Some(Timestamp::Modified), config.settings.max_entries_by);
+        assert!(!config.settings.gtk_max_age);
+        assert_eq!(
+            vec![String::from("smb"), String::from("davs")],
+            config.settings.schemes
+        );
+        assert_eq!(None, config.settings.unknown_schemes);
+        assert_eq!(
+            vec!["empty", "work"],
+            config.profiles.keys().collect::<Vec<_>>()
+        );
+        let work = &config.profiles["work"];
+        assert_eq!(2, work.rules.len());
+        assert_eq!(Some(100), work.max_entries);
+        assert!(work.gtk_max_age);
+        assert_eq!(Some(UnknownSchemes::Warn), work.unknown_schemes);
+    }
+
+    #[test]
+    fn parse_errors() {
+        for (contents, message) in [
+            ("rules = [\"glob:/home/[\"]", "glob:/home/["),
+            ("rules = \"/tmp\"", "expected a sequence"),
+            ("max-entries = -1", "max-entries"),
+            ("max-entries-by = \"opened\"", "opened"),
+            ("unknown-schemes = \"ignore\"", "ignore"),
+            ("prune-app = [\"vlc\"]", "unknown field `prune-app`"),
+            ("[profiles.work]\nrule = [\"/tmp\"]", "unknown field `rule`"),
+            ("rules = [\"/tmp\"", "rules"),
+        ] {
+            let error = Config::parse(contents).unwrap_err().to_string();
+            assert!(
+                error.contains(message),
+                "{error:?} should contain {message:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn extend() {
+        let mut settings = Settings {
+            rules: vec!["/tmp".parse().unwrap()],
+            prune_apps: vec![String::from("vlc")],
+            max_entries: Some(100),
+            max_entries_by: Some(Timestamp::Modified),
+            gtk_max_age: true,
+            schemes: vec![String::from("smb")],
+            unknown_schemes: Some(UnknownSchemes::Warn),
+        };
+        settings.extend(Settings {
+            rules: vec!["keep:/tmp/keep".parse().unwrap()],
+            max_entries: Some(10),
+            schemes: vec![String::from("davs")],
+            ..Default::default()
+        });
+        assert_eq!(2, settings.rules.len());
+        assert!(matches!(settings.rules[1], Rule::Keep(_)));
+        assert_eq!(vec![String::from("vlc")], settings.prune_apps);
+        assert_eq!(Some(10), settings.max_entries);
+        assert_eq!(Some(Timestamp::Modified), settings.max_entries_by);
+        assert!(settings.gtk_max_age);
+        assert_eq!(
+            vec![String::from("smb"), String::from("davs")],
+            settings.schemes
+        );
+        assert_eq!(Some(UnknownSchemes::Warn), settings.unknown_schemes);
+    }
+
+    #[test]
+    fn load_profiles() {
+        let path = env::temp_dir().join(format!(
+            "clean-recently-used-test-config-{}.toml",
+            process::id()
+        ));
+        fs::write(
+            &path,
+            "rules = [\"/tmp\"]\n[profiles.work]\nrules = [\"/home/me/work\"]\n",
+        )
+        .unwrap();
+        assert_eq!(1, load(Some(&path), None).unwrap().rules.len());
+        assert_eq!(2, load(Some(&path), Some("work")).unwrap().rules.len());
+        assert!(load(Some(&path), Some("home")).is_err());
+        fs::remove_file(&path).unwrap();
+        assert!(load(Some(&path), None).is_err());
+    }
+}
@@ -0,0 +1,71 @@
+use similar::TextDiff;
+use std::io::{self, Write};
+
+/// Write a unified diff between the original and the filtered document.
+///
+/// Both are shown as text even if they are not valid UTF-8,
+/// just like the filter tolerates invalid UTF-8 in hrefs.
+pub fn write_unified(
+    original: &[u8],
+    filtered: &[u8],
+    name: &str,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    let original = String::from_utf8_lossy(original);
+    let filtered = String::from_utf8_lossy(filtered);
+    TextDiff::from_lines(&original, &filtered)
+        .unified_diff()
+        .header(name, name)
+        .to_writer(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn unified() {
+        let original = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xbel version="1.0">
+  <bookmark href="file:///home/me/a.txt" added="2025-01-01T00:00:00Z" modified="2025-01-01T00:00:00Z" visited="2025-01-01T00:00:00Z">
+  </bookmark>
+  <bookmark href="file:///tmp/b.txt" added="2025-01-01T00:00:00Z" modified="2025-01-01T00:00:00Z" visited="2025-01-01T00:00:00Z">
+  </bookmark>
+</xbel>
+"#;
+        let filtered = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xbel version="1.0">
+  <bookmark href="file:///home/me/a.txt" added="2025-01-01T00:00:00Z" modified="2025-01-01T00:00:00Z" visited="2025-01-01T00:00:00Z">
+  </bookmark>
+</xbel>
+"#;
+        let expected = r#"--- recently-used.xbel
++++ recently-used.xbel
+@@ -2,6 +2,4 @@
+ <xbel version="1.0">
+   <bookmark href="file:///home/me/a.txt" added="2025-01-01T00:00:00Z" modified="2025-01-01T00:00:00Z" visited="2025-01-01T00:00:00Z">
+   </bookmark>
+-  <bookmark href="file:///tmp/b.txt" added="2025-01-01T00:00:00Z" modified="2025-01-01T00:00:00Z" visited="2025-01-01T00:00:00Z">
+-  </bookmark>
+ </xbel>
+"#;
+        let mut output = Vec::new();
+        write_unified(
+            original.as_bytes(),
+            filtered.as_bytes(),
+            "recently-used.xbel",
+            &mut output,
+        )
+        .unwrap();
+        assert_eq!(expected, String::from_utf8(output).unwrap());
+    }
+
+    #[test]
+    fn unchanged() {
+        let document = b"<xbel version=\"1.0\">\n</xbel>\n";
+        let mut output = Vec::new();
+        write_unified(document, document, "recently-used.xbel", &mut output).unwrap();
+        assert!(output.is_empty());
+    }
+}
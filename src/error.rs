@@ -0,0 +1,91 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+/// An error reading, filtering or writing a list of recently used files.
+///
+/// Positions are byte offsets into the document being read.
+#[derive(Debug)]
+pub enum XbelError {
+    /// The home directory, and with it the default list, could not be determined.
+    NoBaseDirs,
+    /// A file could not be read or written (or, without a path, standard input or output).
+    Io {
+        path: Option<PathBuf>,
+        error: io::Error,
+    },
+    /// The document is not well-formed XML.
+    Xml {
+        position: u64,
+        error: quick_xml::Error,
+    },
+    /// A bookmark does not have exactly one href attribute.
+    BookmarkWithoutSingleHref { position: u64 },
+    /// A bookmark start tag without a matching end tag.
+    UnterminatedBookmark { position: u64, href: String },
+    /// A bookmark whose href is neither a local file nor one of the known other schemes.
+    HrefNotRecognized { position: u64, href: String },
+}
+
+impl XbelError {
+    /// An I/O error on standard input or output, or a stream without a known path.
+    pub fn io(error: io::Error) -> Self {
+        XbelError::Io { path: None, error }
+    }
+
+    /// An I/O error on the given file.
+    pub fn file(path: impl Into<PathBuf>, error: io::Error) -> Self {
+        XbelError::Io {
+            path: Some(path.into()),
+            error,
+        }
+    }
+}
+
+impl fmt::Display for XbelError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            XbelError::NoBaseDirs => {
+                write!(
+                    f,
+                    "NoBaseDirsError: the home directory could not be determined"
+                )
+            }
+            XbelError::Io {
+                path: Some(path),
+                error,
+            } => write!(f, "IoError: {}: {error}", path.display()),
+            XbelError::Io { path: None, error } => write!(f, "IoError: {error}"),
+            XbelError::Xml { position, error } => {
+                write!(f, "XmlError at byte {position}: {error}")
+            }
+            XbelError::BookmarkWithoutSingleHref { position } => write!(
+                f,
+                "BookmarkWithoutSingleHrefError at byte {position}: a bookmark must have exactly one href"
+            ),
+            XbelError::UnterminatedBookmark { position, href } => {
+                write!(f, "UnterminatedBookmarkError at byte {position}: {href}")
+            }
+            XbelError::HrefNotRecognized { position, href } => {
+                write!(f, "HrefNotRecognizedError at byte {position}: {href}")
+            }
+        }
+    }
+}
+
+impl Error for XbelError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            XbelError::Io { error, .. } => Some(error),
+            XbelError::Xml { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for XbelError {
+    fn from(error: io::Error) -> Self {
+        XbelError::io(error)
+    }
+}
@@ -0,0 +1,145 @@
+use chrono::TimeDelta;
+use directories::BaseDirs;
+use std::env;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug)]
+pub struct InvalidGtkSettingError {
+    path: PathBuf,
+    value: String,
+}
+impl fmt::Display for InvalidGtkSettingError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "InvalidGtkSettingError: {}: gtk-recent-files-max-age = {}",
+            self.path.display(),
+            self.value
+        )
+    }
+}
+impl Error for InvalidGtkSettingError {}
+
+const MAX_AGE_KEY: &str = "gtk-recent-files-max-age";
+
+/// The `settings.ini` files GTK reads, most important first:
+/// the user’s own settings for GTK 4 and GTK 3, then the system-wide ones.
+fn settings_files() -> Vec<PathBuf> {
+    let mut config_dirs = Vec::new();
+    if let Some(base_dirs) = BaseDirs::new() {
+        config_dirs.push(base_dirs.config_dir().to_path_buf());
+    }
+    match env::var_os("XDG_CONFIG_DIRS") {
+        Some(dirs) if !dirs.is_empty() => config_dirs.extend(env::split_paths(&dirs)),
+        _ => config_dirs.push(PathBuf::from("/etc/xdg")),
+    }
+    config_dirs.push(PathBuf::from("/etc"));
+    config_dirs
+        .iter()
+        .flat_map(|dir| [dir.join("gtk-4.0"), dir.join("gtk-3.0")])
+        .map(|dir| dir.join("settings.ini"))
+        .collect()
+}
+
+/// Find the value of `key` in the `[Settings]` group of a GTK `settings.ini` file.
+fn setting<'a>(contents: &'a str, key: &str) -> Option<&'a str> {
+    let mut in_settings = false;
+    let mut value = None;
+    for line in contents.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_settings = line == "[Settings]";
+        } else if in_settings
+            && let Some((k, v)) = line.split_once('=')
+            && k.trim() == key
+        {
+            value = Some(v.trim());
+        }
+    }
+    value
+}
+
+/// Read GTK’s `gtk-recent-files-max-age` setting from the first of `paths` that sets it.
+/// The setting is in days, and -1 (or any negative value) means no limit, returned as `None`.
+/// If no file sets it, there is no limit either.
+fn max_age_from(paths: &[impl AsRef<Path>]) -> Result<Option<TimeDelta>, Box<dyn Error>> {
+    for path in paths {
+        let path = path.as_ref();
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == ErrorKind::NotFound => continue,
+            Err(e) => return Err(Box::new(e)),
+        };
+        let Some(value) = setting(&contents, MAX_AGE_KEY) else {
+            continue;
+        };
+        let days: i64 = value.parse().map_err(|_| InvalidGtkSettingError {
+            path: path.to_path_buf(),
+            value: value.to_string(),
+        })?;
+        return Ok(if days < 0 {
+            None
+        } else {
+            TimeDelta::try_days(days)
+        });
+    }
+    Ok(None)
+}
+
+/// Read GTK’s `gtk-recent-files-max-age` setting, see [`max_age_from`].
+pub fn recent_files_max_age() -> Result<Option<TimeDelta>, Box<dyn Error>> {
+    max_age_from(&settings_files())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process;
+
+    #[test]
+    fn settings() {
+        let contents = "# a comment
+gtk-recent-files-max-age = 1
+
+[Settings]
+gtk-application-prefer-dark-theme=true
+gtk-recent-files-max-age = 30
+
+[Other]
+gtk-recent-files-max-age=7
+";
+        assert_eq!(Some("30"), setting(contents, MAX_AGE_KEY));
+        assert_eq!(
+            Some("true"),
+            setting(contents, "gtk-application-prefer-dark-theme")
+        );
+        assert_eq!(None, setting(contents, "gtk-recent-files-enabled"));
+    }
+
+    #[test]
+    fn max_age() {
+        let dir = env::temp_dir().join(format!("clean-recently-used-test-gtk-{}", process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let missing = dir.join("missing.ini");
+        let unset = dir.join("unset.ini");
+        fs::write(&unset, "[Settings]\ngtk-theme-name=Adwaita\n").unwrap();
+        let thirty = dir.join("thirty.ini");
+        fs::write(&thirty, "[Settings]\ngtk-recent-files-max-age=30\n").unwrap();
+        let unlimited = dir.join("unlimited.ini");
+        fs::write(&unlimited, "[Settings]\ngtk-recent-files-max-age=-1\n").unwrap();
+        let invalid = dir.join("invalid.ini");
+        fs::write(&invalid, "[Settings]\ngtk-recent-files-max-age=thirty\n").unwrap();
+
+        assert_eq!(None, max_age_from(&[&missing, &unset]).unwrap());
+        assert_eq!(
+            Some(TimeDelta::days(30)),
+            max_age_from(&[&missing, &unset, &thirty, &unlimited]).unwrap()
+        );
+        assert_eq!(None, max_age_from(&[&unlimited, &thirty]).unwrap());
+        assert!(max_age_from(&[&invalid, &thirty]).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
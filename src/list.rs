@@ -0,0 +1,302 @@
+use crate::bookmark::{Bookmark, Timestamp};
+use crate::rules::Filter;
+use chrono::{DateTime, SecondsFormat, Utc};
+use clap::ValueEnum;
+use serde::Serialize;
+use std::cmp::Reverse;
+use std::error::Error;
+use std::io::Write;
+
+/// How the list command prints the entries.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum ListFormat {
+    /// Aligned columns, with the path (or the href, for entries that are not local files).
+    #[default]
+    Table,
+    /// Tab-separated values, with a header line.
+    Tsv,
+    /// A JSON array of objects.
+    Json,
+}
+
+/// Select the bookmarks matching the rules of the filter (all of them if there are no rules),
+/// sorted by the given timestamp, newest first, or in document order.
+pub fn select(bookmarks: Vec<Bookmark>, filter: &Filter, sort: Option<Timestamp>) -> Vec<Bookmark> {
+    let mut bookmarks: Vec<_> = bookmarks
+        .into_iter()
+        .filter(|bookmark| filter.rules.is_empty() || filter.selects(bookmark))
+        .collect();
+    if let Some(timestamp) = sort {
+        // entries without the timestamp sort last
+        bookmarks.sort_by_key(|bookmark| Reverse(bookmark.timestamp(timestamp)));
+    }
+    bookmarks
+}
+
+#[derive(Debug, Serialize)]
+struct Application<'a> {
+    name: &'a str,
+    count: u64,
+}
+
+/// A bookmark as it is printed.
+#[derive(Debug, Serialize)]
+struct Entry<'a> {
+    href: &'a str,
+    path: Option<&'a str>,
+    mime_type: Option<&'a str>,
+    applications: Vec<Application<'a>>,
+    groups: &'a [String],
+    added: Option<String>,
+    modified: Option<String>,
+    visited: Option<String>,
+}
+
+fn format_timestamp(timestamp: Option<DateTime<Utc>>) -> Option<String> {
+    timestamp.map(|timestamp| timestamp.to_rfc3339_opts(SecondsFormat::Secs, true))
+}
+
+impl<'a> From<&'a Bookmark> for Entry<'a> {
+    fn from(bookmark: &'a Bookmark) -> Self {
+        Entry {
+            href: &bookmark.encoded_href,
+            path: bookmark.path(),
+            mime_type: bookmark.mime_type.as_deref(),
+            applications: bookmark
+                .applications
+                .iter()
+                .map(|application| Application {
+                    name: &application.name,
+                    count: application.count,
+                })
+                .collect(),
+            groups: &bookmark.groups,
+            added: format_timestamp(bookmark.added),
+            modified: format_timestamp(bookmark.modified),
+            visited: format_timestamp(bookmark.visited),
+        }
+    }
+}
+
+impl Entry<'_> {
+    fn applications(&self) -> String {
+        self.applications
+            .iter()
+            .map(|application| format!("{}:{}", application.name, application.count))
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+}
+
+/// Escape a TSV field, so that it cannot contain a tab or newline.
+fn escape_tsv(field: &str) -> String {
+    field
+        .replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
+
+fn write_table(entries: &[Entry], out: &mut impl Write) -> Result<(), Box<dyn Error>> {
+    let mut rows = vec![
+        [
+            "VISITED",
+            "MODIFIED",
+            "ADDED",
+            "MIME TYPE",
+            "APPLICATIONS",
+            "GROUPS",
+            "LOCATION",
+        ]
+        .map(String::from),
+    ];
+    for entry in entries {
+        let or_dash = |value: Option<&str>| value.unwrap_or("-").to_string();
+        let or_dash_if_empty = |value: String| {
+            if value.is_empty() {
+                String::from("-")
+            } else {
+                value
+            }
+        };
+        rows.push([
+            or_dash(entry.visited.as_deref()),
+            or_dash(entry.modified.as_deref()),
+            or_dash(entry.added.as_deref()),
+            or_dash(entry.mime_type),
+            or_dash_if_empty(entry.applications()),
+            or_dash_if_empty(entry.groups.join(",")),
+            entry.path.unwrap_or(entry.href).to_string(),
+        ]);
+    }
+    let mut widths = [0; 7];
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    for row in &rows {
+        // the last column is not padded, to avoid trailing whitespace
+        for (cell, width) in row.iter().zip(widths).take(row.len() - 1) {
+            write!(out, "{cell:width$}  ")?;
+        }
+        writeln!(out, "{}", row[row.len() - 1])?;
+    }
+    Ok(())
+}
+
+fn write_tsv(entries: &[Entry], out: &mut impl Write) -> Result<(), Box<dyn Error>> {
+    writeln!(
+        out,
+        "href\tpath\tmime_type\tapplications\tgroups\tadded\tmodified\tvisited"
+    )?;
+    for entry in entries {
+        let fields = [
+            entry.href.to_string(),
+            entry.path.unwrap_or_default().to_string(),
+            entry.mime_type.unwrap_or_default().to_string(),
+            entry.applications(),
+            entry.groups.join(","),
+            entry.added.clone().unwrap_or_default(),
+            entry.modified.clone().unwrap_or_default(),
+            entry.visited.clone().unwrap_or_default(),
+        ];
+        writeln!(out, "{}", fields.map(|field| escape_tsv(&field)).join("\t"))?;
+    }
+    Ok(())
+}
+
+/// Print the bookmarks in the given format.
+pub fn write(
+    bookmarks: &[Bookmark],
+    format: ListFormat,
+    out: &mut impl Write,
+) -> Result<(), Box<dyn Error>> {
+    let entries: Vec<Entry> = bookmarks.iter().map(Entry::from).collect();
+    match format {
+        ListFormat::Table => write_table(&entries, out),
+        ListFormat::Tsv => write_tsv(&entries, out),
+        ListFormat::Json => {
+            serde_json::to_writer_pretty(&mut *out, &entries)?;
+            writeln!(out)?;
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bookmark;
+    use pretty_assertions::assert_eq;
+
+    const INPUT: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xbel version="1.0"
+      xmlns:bookmark="http://www.freedesktop.org/standards/desktop-bookmarks"
+      xmlns:mime="http://www.freedesktop.org/standards/shared-mime-info"
+>
+  <bookmark href="file:///home/me/A%20File.txt" added="2020-09-24T20:00:00Z" modified="2020-09-25T20:00:00Z" visited="2020-09-25T20:00:00Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="text/plain"/>
+        <bookmark:groups>
+          <bookmark:group>gedit</bookmark:group>
+        </bookmark:groups>
+        <bookmark:applications>
+          <bookmark:application name="gedit" exec="&apos;gedit %u&apos;" modified="2020-09-25T20:00:00Z" count="3"/>
+          <bookmark:application name="org.gnome.TextEditor" exec="&apos;gnome-text-editor %U&apos;" modified="2020-09-25T20:00:00Z" count="1"/>
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>
+  <bookmark href="sftp://me@example.com/tmp/B-File.txt" added="2020-09-26T20:00:00Z" modified="2020-09-26T20:00:00Z" visited="2020-09-26T20:00:00Z">
+  </bookmark>
+  <bookmark href="file:///tmp/C%09File.txt" added="2020-09-27T20:00:00Z" modified="2020-09-27T20:00:00Z">
+  </bookmark>
+</xbel>
+"#;
+
+    fn list(rules: &[&str], sort: Option<Timestamp>, format: ListFormat) -> String {
+        let bookmarks = bookmark::read_all(INPUT.as_bytes()).unwrap();
+        let filter = Filter::from(
+            rules
+                .iter()
+                .map(|rule| rule.parse().unwrap())
+                .collect::<Vec<_>>(),
+        );
+        let bookmarks = select(bookmarks, &filter, sort);
+        let mut output = Vec::new();
+        write(&bookmarks, format, &mut output).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn table() {
+        let expected = "\
+VISITED               MODIFIED              ADDED                 MIME TYPE   APPLICATIONS                    GROUPS  LOCATION
+2020-09-25T20:00:00Z  2020-09-25T20:00:00Z  2020-09-24T20:00:00Z  text/plain  gedit:3,org.gnome.TextEditor:1  gedit   /home/me/A File.txt
+2020-09-26T20:00:00Z  2020-09-26T20:00:00Z  2020-09-26T20:00:00Z  -           -                               -       sftp://me@example.com/tmp/B-File.txt
+-                     2020-09-27T20:00:00Z  2020-09-27T20:00:00Z  -           -                               -       /tmp/C\tFile.txt
+";
+        assert_eq!(expected, list(&[], None, ListFormat::Table));
+    }
+
+    #[test]
+    fn tsv() {
+        let expected = "\
+href\tpath\tmime_type\tapplications\tgroups\tadded\tmodified\tvisited
+file:///tmp/C%09File.txt\t/tmp/C\\tFile.txt\t\t\t\t2020-09-27T20:00:00Z\t2020-09-27T20:00:00Z\t
+file:///home/me/A%20File.txt\t/home/me/A File.txt\ttext/plain\tgedit:3,org.gnome.TextEditor:1\tgedit\t2020-09-24T20:00:00Z\t2020-09-25T20:00:00Z\t2020-09-25T20:00:00Z
+";
+        assert_eq!(
+            expected,
+            list(&["/home", "/tmp"], Some(Timestamp::Added), ListFormat::Tsv)
+        );
+    }
+
+    #[test]
+    fn json() {
+        let expected = r#"[
+  {
+    "href": "sftp://me@example.com/tmp/B-File.txt",
+    "path": null,
+    "mime_type": null,
+    "applications": [],
+    "groups": [],
+    "added": "2020-09-26T20:00:00Z",
+    "modified": "2020-09-26T20:00:00Z",
+    "visited": "2020-09-26T20:00:00Z"
+  },
+  {
+    "href": "file:///home/me/A%20File.txt",
+    "path": "/home/me/A File.txt",
+    "mime_type": "text/plain",
+    "applications": [
+      {
+        "name": "gedit",
+        "count": 3
+      },
+      {
+        "name": "org.gnome.TextEditor",
+        "count": 1
+      }
+    ],
+    "groups": [
+      "gedit"
+    ],
+    "added": "2020-09-24T20:00:00Z",
+    "modified": "2020-09-25T20:00:00Z",
+    "visited": "2020-09-25T20:00:00Z"
+  }
+]
+"#;
+        assert_eq!(
+            expected,
+            list(
+                &["regex:.", "keep:/tmp"],
+                Some(Timestamp::Visited),
+                ListFormat::Json
+            )
+        );
+    }
+}
@@ -1,178 +1,486 @@
+mod bookmark;
+mod cli;
+mod config;
+mod diff;
+mod error;
+mod gtk;
+mod list;
+mod remote;
+mod report;
+mod rules;
+mod stats;
+
+use crate::bookmark::{Bookmark, Timestamp};
+use crate::cli::{CleanArgs, Cli, Command, InputArgs, ListArgs};
+use crate::config::Settings;
+use crate::error::XbelError;
+use crate::report::{Removal, Report, Summary};
+use crate::rules::{Filter, Reason, UnknownSchemes};
+use crate::stats::Stats;
 use chrono::prelude::*;
+use clap::Parser;
 use directories::BaseDirs;
-use itertools::Itertools;
-use percent_encoding::percent_decode;
-use quick_xml::events::attributes::{Attribute, Attributes};
 use quick_xml::events::Event;
 use quick_xml::name::QName;
 use quick_xml::{Reader, Writer};
-use std::borrow::Cow;
 use std::env;
 use std::error::Error;
 use std::fmt;
-use std::fs::{rename, File, OpenOptions};
-use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::fs::{self, File, OpenOptions, hard_link, remove_file, rename};
+use std::io::{self, BufRead, BufReader, BufWriter, ErrorKind, Read, Write};
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
 use std::result::Result;
 use std::str;
 use std::vec::Vec;
 
-#[derive(Debug)]
-struct NoBaseDirsError;
-impl fmt::Display for NoBaseDirsError {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "NoBaseDirsError")
+/// A part of an XBEL document: either a whole bookmark, or any other event.
+enum Item {
+    Bookmark {
+        bookmark: Bookmark,
+        /// Why the bookmark is removed, or `None` if it is kept.
+        removed: Option<Reason>,
+    },
+    Event(Event<'static>),
+}
+
+/// Writes the items of a filtered document,
+/// dropping removed bookmarks along with the whitespace preceding them.
+struct ItemWriter<W: Write> {
+    writer: Writer<W>,
+    /// Whitespace that is only written if it is not followed by a removed bookmark.
+    whitespace: Option<Event<'static>>,
+    summary: Summary,
+}
+
+impl<W: Write> ItemWriter<W> {
+    fn write(&mut self, item: Item) -> Result<(), XbelError> {
+        match item {
+            Item::Bookmark {
+                bookmark,
+                removed: Some(reason),
+            } => {
+                self.whitespace = None;
+                self.summary.removed.push(Removal {
+                    path: bookmark.path().map(String::from),
+                    href: bookmark.encoded_href,
+                    reason,
+                });
+                return Ok(());
+            }
+            Item::Event(Event::Text(e))
+                if e.decode()
+                    .is_ok_and(|text| text.chars().all(char::is_whitespace)) =>
+            {
+                self.flush_whitespace()?;
+                self.whitespace = Some(Event::Text(e));
+                return Ok(());
+            }
+            _ => (),
+        }
+        self.flush_whitespace()?;
+        match item {
+            Item::Bookmark { bookmark, .. } => {
+                self.summary.kept += 1;
+                for event in bookmark.events {
+                    self.writer.write_event(event)?;
+                }
+            }
+            Item::Event(event) => {
+                self.writer.write_event(event)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn flush_whitespace(&mut self) -> Result<(), XbelError> {
+        if let Some(whitespace) = self.whitespace.take() {
+            self.writer.write_event(whitespace)?;
+        }
+        Ok(())
+    }
+
+    fn finish(mut self) -> Result<Summary, XbelError> {
+        self.flush_whitespace()?;
+        self.writer.into_inner().flush()?;
+        Ok(self.summary)
+    }
+}
+
+/// Remove all but the `max_entries` newest of the kept bookmarks.
+/// Bookmarks without a (valid) timestamp count as the oldest;
+/// among bookmarks with the same timestamp, earlier ones are kept.
+fn limit_entries(items: &mut [Item], max_entries: usize, newest_by: Timestamp) {
+    let mut kept: Vec<_> = items
+        .iter_mut()
+        .filter_map(|item| match item {
+            Item::Bookmark {
+                bookmark,
+                removed: removed @ None,
+            } => Some((bookmark.timestamp(newest_by), removed)),
+            _ => None,
+        })
+        .collect();
+    kept.sort_by(|(a, _), (b, _)| b.cmp(a));
+    for (_, removed) in kept.into_iter().skip(max_entries) {
+        *removed = Some(Reason::MaxEntries);
+    }
+}
+
+fn read_filter_write<R: BufRead, W: Write>(
+    reader: R,
+    writer: W,
+    filter: &Filter,
+) -> Result<Summary, XbelError> {
+    let mut reader = Reader::from_reader(reader);
+    let mut buf = Vec::new();
+
+    let mut writer = ItemWriter {
+        writer: Writer::new(writer),
+        whitespace: None,
+        summary: Summary::default(),
+    };
+
+    // with a maximum number of entries, the whole document must be read before anything is written;
+    // otherwise, each item is written as soon as it has been read
+    let mut items = filter.max_entries.map(|_| Vec::new());
+
+    loop {
+        let position = reader.buffer_position();
+        let item = match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(e)) if e.name() == QName(b"bookmark") => {
+                let mut bookmark = Bookmark::read(&mut reader, e.into_owned(), position)?;
+                let apply = |bookmark: &mut Bookmark| {
+                    filter
+                        .apply(bookmark)
+                        .map_err(|error| XbelError::Xml { position, error })
+                };
+                let removed = match filter.unknown_schemes {
+                    _ if filter.knows_scheme(&bookmark.href) => apply(&mut bookmark)?,
+                    UnknownSchemes::Fail => {
+                        return Err(XbelError::HrefNotRecognized {
+                            position,
+                            href: bookmark.href,
+                        });
+                    }
+                    UnknownSchemes::Keep => apply(&mut bookmark)?,
+                    UnknownSchemes::Warn => {
+                        eprintln!(
+                            "clean-recently-used: warning: unknown scheme at byte {position}: {}",
+                            bookmark.href
+                        );
+                        apply(&mut bookmark)?
+                    }
+                    UnknownSchemes::Remove => match apply(&mut bookmark)? {
+                        None if filter.keeps(&bookmark) => None,
+                        removed => removed.or(Some(Reason::UnknownScheme)),
+                    },
+                };
+                Item::Bookmark { bookmark, removed }
+            }
+            Ok(Event::Eof) => break,
+            // everything else (including comments, processing instructions and so on) is kept as it is
+            Ok(event) => Item::Event(event.into_owned()),
+            Err(error) => {
+                return Err(XbelError::Xml {
+                    position: reader.error_position(),
+                    error,
+                });
+            }
+        };
+        match &mut items {
+            Some(items) => items.push(item),
+            None => writer.write(item)?,
+        }
+        buf.clear();
+    }
+    if let (Some(mut items), Some(max_entries)) = (items, filter.max_entries) {
+        limit_entries(&mut items, max_entries, filter.max_entries_by);
+        for item in items {
+            writer.write(item)?;
+        }
     }
+    writer.finish()
 }
-impl Error for NoBaseDirsError {}
 
 #[derive(Debug)]
-struct BookmarkWithoutSingleHrefError;
-impl fmt::Display for BookmarkWithoutSingleHrefError {
+struct NoBackupError;
+impl fmt::Display for NoBackupError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "BookmarkWithoutSingleHrefError")
+        write!(f, "NoBackupError")
     }
 }
-impl Error for BookmarkWithoutSingleHrefError {}
+impl Error for NoBackupError {}
 
+/// Two outputs of a clean that would both be written to standard output, mixing them up.
 #[derive(Debug)]
-struct HrefNotRecognizedError {
-    href: String,
+struct StdoutConflictError {
+    first: &'static str,
+    second: &'static str,
 }
-impl fmt::Display for HrefNotRecognizedError {
+impl fmt::Display for StdoutConflictError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "HrefNotRecognizedError: {}", self.href)
+        write!(
+            f,
+            "StdoutConflictError: {} and {} cannot both be written to standard output",
+            self.first, self.second
+        )
     }
 }
-impl Error for HrefNotRecognizedError {}
-
-fn href_attribute(attributes: Attributes) -> Result<Cow<[u8]>, BookmarkWithoutSingleHrefError> {
-    attributes
-        .filter_map(|a| match a {
-            Ok(Attribute {
-                key: QName(b"href"),
-                value,
-            }) => Some(value),
-            _ => None,
-        })
-        .exactly_one()
-        .map_err(|_e| BookmarkWithoutSingleHrefError)
+impl Error for StdoutConflictError {}
+
+/// The list of recently used files of the current user.
+fn default_input() -> Result<PathBuf, XbelError> {
+    let base_dirs = BaseDirs::new().ok_or(XbelError::NoBaseDirs)?;
+    Ok(base_dirs.data_dir().join("recently-used.xbel"))
 }
 
-fn path_needs_cleaning(paths_to_clean: &[String], path: &str) -> bool {
-    paths_to_clean
-        .iter()
-        .any(|path_to_clean| path.starts_with(path_to_clean))
+/// Whether the path stands for standard input or output.
+fn is_stdio(path: &Path) -> bool {
+    path == Path::new("-")
 }
 
-fn read_filter_write<R: BufRead, W: Write>(
-    reader: R,
-    writer: W,
-    paths_to_clean: &[String],
-) -> Result<(), Box<dyn Error>> {
-    let mut reader = Reader::from_reader(reader);
-    let mut buf = Vec::new();
+fn open_input(path: &Path) -> Result<Box<dyn BufRead>, XbelError> {
+    if is_stdio(path) {
+        Ok(Box::new(io::stdin().lock()))
+    } else {
+        let file = File::open(path).map_err(|error| XbelError::file(path, error))?;
+        Ok(Box::new(BufReader::new(file)))
+    }
+}
 
-    let mut writer = Writer::new(writer);
+/// Whether both paths refer to the same existing file.
+fn same_file(a: &Path, b: &Path) -> bool {
+    match (fs::metadata(a), fs::metadata(b)) {
+        (Ok(a), Ok(b)) => a.dev() == b.dev() && a.ino() == b.ino(),
+        _ => false,
+    }
+}
 
-    let mut skipping = false;
-    let mut skip_whitespace = false;
+/// Write the output to a new file (or to standard output if the filename is `-`),
+/// removing the file again if writing it fails, so that no partial output is left behind.
+fn write_output<T>(
+    filename: &Path,
+    write: impl FnOnce(&mut dyn Write) -> Result<T, XbelError>,
+) -> Result<T, XbelError> {
+    if is_stdio(filename) {
+        return write(&mut io::stdout().lock());
+    }
+    let file = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(filename)
+        .map_err(|error| XbelError::file(filename, error))?;
+    let result = write(&mut BufWriter::new(file));
+    if result.is_err() {
+        // the original error is more interesting than any error removing the file
+        let _ = remove_file(filename);
+    }
+    result
+}
 
-    loop {
-        if skipping {
-            match reader.read_event_into(&mut buf) {
-                Ok(Event::End(e)) if e.name() == QName(b"bookmark") => {
-                    skipping = false;
-                    skip_whitespace = true;
+/// Replace the file with the temporary one, keeping a backup of it if asked to.
+/// If that fails, the temporary file is removed and the file is left unchanged.
+fn replace(filename: &Path, temporary_filename: &Path, backup: bool) -> Result<(), XbelError> {
+    let replace = || {
+        if backup {
+            let backup_filename = with_suffix(filename, ".bak");
+            match remove_file(&backup_filename) {
+                Err(e) if e.kind() != ErrorKind::NotFound => {
+                    return Err(XbelError::file(backup_filename, e));
                 }
                 _ => (),
             }
+            hard_link(filename, &backup_filename)
+                .map_err(|error| XbelError::file(&backup_filename, error))?;
+        }
+        rename(temporary_filename, filename).map_err(|error| XbelError::file(filename, error))
+    };
+    let result = replace();
+    if result.is_err() {
+        // the original error is more interesting than any error removing the file
+        let _ = remove_file(temporary_filename);
+    }
+    result
+}
+
+/// A path next to the given one, with a suffix appended to the file name.
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(suffix);
+    path.with_file_name(file_name)
+}
+
+/// Check that at most one of the outputs of a clean goes to standard output.
+fn check_stdout(args: &CleanArgs, output_filename: &Path) -> Result<(), StdoutConflictError> {
+    let mut outputs = [
+        (!args.dry_run && is_stdio(output_filename)).then_some("the cleaned list"),
+        report::is_stdout(&args.report).then_some("the report"),
+        args.diff.then_some("the diff"),
+    ]
+    .into_iter()
+    .flatten();
+    match (outputs.next(), outputs.next()) {
+        (Some(first), Some(second)) => Err(StdoutConflictError { first, second }),
+        _ => Ok(()),
+    }
+}
+
+fn clean(args: CleanArgs) -> Result<(), Box<dyn Error>> {
+    let input_filename = match &args.input.input {
+        Some(input) => input.clone(),
+        None => default_input()?,
+    };
+    let output_filename = match &args.output {
+        Some(output) => output.clone(),
+        None if is_stdio(&input_filename) => PathBuf::from("-"),
+        None => input_filename.clone(),
+    };
+    check_stdout(&args, &output_filename)?;
+    // the output is first written to a new file, which then replaces the output file
+    // (after keeping a backup of it, if cleaning in place and asked to),
+    // so that the output file is left unchanged if anything fails
+    let in_place = !is_stdio(&output_filename) && same_file(&input_filename, &output_filename);
+    let temporary_filename = (!is_stdio(&output_filename))
+        .then(|| with_suffix(&output_filename, &Local::now().format("-%+").to_string()));
+    let output_target = temporary_filename.as_deref().unwrap_or(&output_filename);
+    let mut settings = config::load(args.config.as_deref(), args.profile.as_deref())?;
+    settings.extend(args.settings);
+    let filter = settings.into_filter()?;
+
+    let summary = if args.diff {
+        let mut original = Vec::new();
+        open_input(&input_filename)?
+            .read_to_end(&mut original)
+            .map_err(|error| XbelError::file(&input_filename, error))?;
+        let mut filtered = Vec::new();
+        let summary = read_filter_write(original.as_slice(), &mut filtered, &filter)?;
+        let name = input_filename.to_string_lossy();
+        diff::write_unified(&original, &filtered, &name, &mut io::stdout().lock())?;
+        if !args.dry_run {
+            write_output(output_target, |output| {
+                output.write_all(&filtered)?;
+                output.flush()?;
+                Ok(())
+            })?;
+        }
+        summary
+    } else {
+        let reader = open_input(&input_filename)?;
+        if args.dry_run {
+            read_filter_write(reader, io::sink(), &filter)?
         } else {
-            match reader.read_event_into(&mut buf) {
-                Ok(Event::Start(e)) => {
-                    if e.name() == QName(b"bookmark") {
-                        let attr = href_attribute(e.attributes())?;
-                        let href = percent_decode(&attr).decode_utf8_lossy();
-                        #[allow(clippy::if_same_then_else)]
-                        if let Some(path) = href.strip_prefix("file://") {
-                            if path_needs_cleaning(paths_to_clean, path) {
-                                skipping = true;
-                                continue;
-                            }
-                        } else if href.starts_with("trash://") {
-                            // do nothing
-                        } else if href.starts_with("mtp://") {
-                            // do nothing
-                        } else if href.starts_with("ftp://") {
-                            // do nothing
-                        } else if href.starts_with("sftp://") {
-                            // do nothing
-                        } else if href.starts_with("cdda://") {
-                            // do nothing
-                        } else {
-                            return Err(Box::new(HrefNotRecognizedError {
-                                href: href.to_string(),
-                            }));
-                        };
-                    }
-                    writer.write_event(Event::Start(e))?;
-                }
-                Ok(Event::End(e)) => {
-                    writer.write_event(Event::End(e))?;
-                }
-                Ok(Event::Empty(e)) => {
-                    writer.write_event(Event::Empty(e))?;
-                }
-                Ok(Event::Text(e)) => {
-                    if skip_whitespace {
-                        skip_whitespace = false;
-                        assert!(e
-                            .decode()?
-                            .chars()
-                            .all(char::is_whitespace));
-                    } else {
-                        writer.write_event(Event::Text(e))?;
-                    }
-                }
-                Ok(Event::Eof) => break,
-                Ok(Event::Decl(e)) => {
-                    writer.write_event(Event::Decl(e))?;
+            write_output(output_target, |output| {
+                read_filter_write(reader, output, &filter)
+            })?
+        }
+    };
+
+    let report_file = match &args.report {
+        Some(report) => match report::open(report, args.report_format) {
+            Ok(report_file) => Some(report_file),
+            Err(error) => {
+                if let Some(temporary_filename) = &temporary_filename {
+                    // the original error is more interesting than any error removing the file
+                    let _ = remove_file(temporary_filename);
                 }
-                Err(e) => panic!("Error at position {}: {:?}", reader.buffer_position(), e),
-                other => unimplemented!("{:?}", other),
+                return Err(Box::new(error));
             }
+        },
+        None => None,
+    };
+    let write_report = |report_file: Option<Box<dyn Write>>| match report_file {
+        Some(mut report_file) => Report::new(&input_filename, args.dry_run, &filter, &summary)
+            .write_to(&mut report_file, args.report_format),
+        None => Ok(()),
+    };
+
+    if args.dry_run {
+        if !args.diff && !report::is_stdout(&args.report) {
+            report::print_dry_run(&summary, &mut io::stdout().lock())?;
         }
+        return write_report(report_file);
     }
-    writer.into_inner().flush()?;
-    Ok(())
+
+    if let Some(temporary_filename) = temporary_filename {
+        replace(
+            &output_filename,
+            &temporary_filename,
+            in_place && args.backup,
+        )?;
+    }
+
+    write_report(report_file)
 }
 
-fn main() -> Result<(), Box<dyn Error>> {
-    let base_dirs = BaseDirs::new().ok_or(NoBaseDirsError)?;
-    let dir = base_dirs.data_dir();
-    let input_filename = dir.join("recently-used.xbel");
-    let output_filename = dir.join(Local::now().format("recently-used.xbel-%+").to_string());
-    let paths_to_clean: Vec<String> = env::args().skip(1).collect();
+fn restore(args: InputArgs) -> Result<(), Box<dyn Error>> {
+    let filename = match args.input {
+        Some(input) => input,
+        None => default_input()?,
+    };
+    let backup_filename = with_suffix(&filename, ".bak");
+
+    match rename(backup_filename, &filename) {
+        Err(e) if e.kind() == ErrorKind::NotFound => Err(Box::new(NoBackupError)),
+        Err(e) => Err(Box::new(XbelError::file(filename, e))),
+        Ok(()) => Ok(()),
+    }
+}
 
-    let input_file = File::open(&input_filename)?;
-    let reader = BufReader::new(input_file);
+fn list(args: ListArgs) -> Result<(), Box<dyn Error>> {
+    let filename = match args.input.input {
+        Some(input) => input,
+        None => default_input()?,
+    };
 
-    let output_file = OpenOptions::new()
-        .write(true)
-        .create_new(true)
-        .open(&output_filename)?;
-    let writer = BufWriter::new(output_file);
+    let bookmarks = bookmark::read_all(open_input(&filename)?)?;
+    let bookmarks = list::select(bookmarks, &Filter::from(args.rules), args.sort);
+    list::write(&bookmarks, args.format, &mut io::stdout().lock())
+}
 
-    read_filter_write(reader, writer, &paths_to_clean)?;
+fn stats(args: InputArgs) -> Result<(), Box<dyn Error>> {
+    let filename = match args.input {
+        Some(input) => input,
+        None => default_input()?,
+    };
 
-    rename(output_filename, input_filename)?;
+    let bookmarks = bookmark::read_all(open_input(&filename)?)?;
+    Stats::new(&bookmarks, Utc::now()).write(&mut io::stdout().lock())?;
+    Ok(())
+}
 
+/// Filter standard input to standard output, without touching any other files.
+fn filter(settings: Settings) -> Result<(), Box<dyn Error>> {
+    let filter = settings.into_filter()?;
+    let writer = BufWriter::new(io::stdout().lock());
+    read_filter_write(io::stdin().lock(), writer, &filter)?;
     Ok(())
 }
 
+fn main() -> ExitCode {
+    let cli = Cli::parse_from(cli::with_default_command(env::args_os()));
+    let result = match cli.command {
+        Command::Clean(args) => clean(args),
+        Command::Restore(args) => restore(args),
+        Command::List(args) => list(args),
+        Command::Stats(args) => stats(args),
+        Command::Filter(settings) => filter(settings),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("clean-recently-used: {error}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use pretty_assertions::assert_eq;
+    use std::process;
 
     #[test]
     fn no_filter() {
@@ -197,7 +505,12 @@ mod tests {
 </xbel>
 "#;
         let mut output = Vec::new();
-        read_filter_write(BufReader::new(input.as_bytes()), &mut output, &[]).unwrap();
+        read_filter_write(
+            BufReader::new(input.as_bytes()),
+            &mut output,
+            &Filter::default(),
+        )
+        .unwrap();
         assert_eq!(input, String::from_utf8(output).unwrap());
     }
 
@@ -253,7 +566,7 @@ mod tests {
         read_filter_write(
             BufReader::new(input.as_bytes()),
             &mut output,
-            &[String::from("/home/a"), String::from("/home/b")],
+            &Filter::from(vec!["/home/a".parse().unwrap(), "/home/b".parse().unwrap()]),
         )
         .unwrap();
         let expected = r#"<?xml version="1.0" encoding="UTF-8"?>
@@ -318,7 +631,7 @@ mod tests {
         read_filter_write(
             BufReader::new(input.as_bytes()),
             &mut output,
-            &[String::from("/tmp")],
+            &Filter::from(vec!["/tmp".parse().unwrap()]),
         )
         .unwrap();
         let expected = r#"<?xml version="1.0" encoding="UTF-8"?>
@@ -383,7 +696,7 @@ mod tests {
         read_filter_write(
             BufReader::new(input.as_bytes()),
             &mut output,
-            &[String::from("/opt/A Directory")],
+            &Filter::from(vec!["/opt/A Directory".parse().unwrap()]),
         )
         .unwrap();
         let expected = r#"<?xml version="1.0" encoding="UTF-8"?>
@@ -410,13 +723,13 @@ mod tests {
     }
 
     #[test]
-    fn tolerate_invalid_utf8() {
+    fn filter_sibling_names() {
         let input = r#"<?xml version="1.0" encoding="UTF-8"?>
 <xbel version="1.0"
       xmlns:bookmark="http://www.freedesktop.org/standards/desktop-bookmarks"
       xmlns:mime="http://www.freedesktop.org/standards/shared-mime-info"
 >
-  <bookmark href="file:///opt/A%20Directory/A-File.txt%BC" added="2022-04-08T20:00:00Z" modified="2022-04-08T20:00:00Z" visited="2022-04-08T20:00:00Z">
+  <bookmark href="file:///home/a" added="2020-09-24T20:00:00Z" modified="2020-09-25T20:00:00Z" visited="2020-09-25T20:00:00Z">
     <info>
       <metadata owner="http://freedesktop.org">
         <mime:mime-type type="text/plain"/>
@@ -424,12 +737,12 @@ mod tests {
           <bookmark:group>gedit</bookmark:group>
         </bookmark:groups>
         <bookmark:applications>
-          <bookmark:application name="gedit" exec="&apos;gedit %u&apos;" modified="2022-04-08T20:00:00Z" count="1234"/>
+          <bookmark:application name="gedit" exec="&apos;gedit %u&apos;" modified="2020-09-25T20:00:00Z" count="1234"/>
         </bookmark:applications>
       </metadata>
     </info>
   </bookmark>
-  <bookmark href="file:///opt/Another%20Directory/Another-File.txt%BC" added="2022-04-08T20:00:00Z" modified="22022-04-08T20:00:00Z" visited="2022-04-08T20:00:00Z">
+  <bookmark href="file:///home/a/A-File.txt" added="2020-09-24T20:00:00Z" modified="2020-09-25T20:00:00Z" visited="2020-09-25T20:00:00Z">
     <info>
       <metadata owner="http://freedesktop.org">
         <mime:mime-type type="text/plain"/>
@@ -437,7 +750,59 @@ mod tests {
           <bookmark:group>gedit</bookmark:group>
         </bookmark:groups>
         <bookmark:applications>
-          <bookmark:application name="gedit" exec="&apos;gedit %u&apos;" modified="2022-04-08T20:00:00Z" count="1234"/>
+          <bookmark:application name="gedit" exec="&apos;gedit %u&apos;" modified="2020-09-25T20:00:00Z" count="1234"/>
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>
+  <bookmark href="file:///home/alice/A-File.txt" added="2020-09-24T20:00:00Z" modified="2020-09-25T20:00:00Z" visited="2020-09-25T20:00:00Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="text/plain"/>
+        <bookmark:groups>
+          <bookmark:group>gedit</bookmark:group>
+        </bookmark:groups>
+        <bookmark:applications>
+          <bookmark:application name="gedit" exec="&apos;gedit %u&apos;" modified="2020-09-25T20:00:00Z" count="1234"/>
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>
+  <bookmark href="file:///tmp/A-File.txt" added="2020-09-24T20:00:00Z" modified="2020-09-25T20:00:00Z" visited="2020-09-25T20:00:00Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="text/plain"/>
+        <bookmark:groups>
+          <bookmark:group>gedit</bookmark:group>
+        </bookmark:groups>
+        <bookmark:applications>
+          <bookmark:application name="gedit" exec="&apos;gedit %u&apos;" modified="2020-09-25T20:00:00Z" count="1234"/>
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>
+  <bookmark href="file:///tmp" added="2020-09-24T20:00:00Z" modified="2020-09-25T20:00:00Z" visited="2020-09-25T20:00:00Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="text/plain"/>
+        <bookmark:groups>
+          <bookmark:group>gedit</bookmark:group>
+        </bookmark:groups>
+        <bookmark:applications>
+          <bookmark:application name="gedit" exec="&apos;gedit %u&apos;" modified="2020-09-25T20:00:00Z" count="1234"/>
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>
+  <bookmark href="file:///tmpfiles/A-File.txt" added="2020-09-24T20:00:00Z" modified="2020-09-25T20:00:00Z" visited="2020-09-25T20:00:00Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="text/plain"/>
+        <bookmark:groups>
+          <bookmark:group>gedit</bookmark:group>
+        </bookmark:groups>
+        <bookmark:applications>
+          <bookmark:application name="gedit" exec="&apos;gedit %u&apos;" modified="2020-09-25T20:00:00Z" count="1234"/>
         </bookmark:applications>
       </metadata>
     </info>
@@ -448,7 +813,7 @@ mod tests {
         read_filter_write(
             BufReader::new(input.as_bytes()),
             &mut output,
-            &[String::from("/opt/A Directory")],
+            &Filter::from(vec!["/home/a".parse().unwrap(), "/tmp/".parse().unwrap()]),
         )
         .unwrap();
         let expected = r#"<?xml version="1.0" encoding="UTF-8"?>
@@ -456,7 +821,7 @@ mod tests {
       xmlns:bookmark="http://www.freedesktop.org/standards/desktop-bookmarks"
       xmlns:mime="http://www.freedesktop.org/standards/shared-mime-info"
 >
-  <bookmark href="file:///opt/Another%20Directory/Another-File.txt%BC" added="2022-04-08T20:00:00Z" modified="22022-04-08T20:00:00Z" visited="2022-04-08T20:00:00Z">
+  <bookmark href="file:///home/alice/A-File.txt" added="2020-09-24T20:00:00Z" modified="2020-09-25T20:00:00Z" visited="2020-09-25T20:00:00Z">
     <info>
       <metadata owner="http://freedesktop.org">
         <mime:mime-type type="text/plain"/>
@@ -464,7 +829,20 @@ mod tests {
           <bookmark:group>gedit</bookmark:group>
         </bookmark:groups>
         <bookmark:applications>
-          <bookmark:application name="gedit" exec="&apos;gedit %u&apos;" modified="2022-04-08T20:00:00Z" count="1234"/>
+          <bookmark:application name="gedit" exec="&apos;gedit %u&apos;" modified="2020-09-25T20:00:00Z" count="1234"/>
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>
+  <bookmark href="file:///tmpfiles/A-File.txt" added="2020-09-24T20:00:00Z" modified="2020-09-25T20:00:00Z" visited="2020-09-25T20:00:00Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="text/plain"/>
+        <bookmark:groups>
+          <bookmark:group>gedit</bookmark:group>
+        </bookmark:groups>
+        <bookmark:applications>
+          <bookmark:application name="gedit" exec="&apos;gedit %u&apos;" modified="2020-09-25T20:00:00Z" count="1234"/>
         </bookmark:applications>
       </metadata>
     </info>
@@ -475,13 +853,13 @@ mod tests {
     }
 
     #[test]
-    fn other_protocols() {
+    fn filter_raw_prefix() {
         let input = r#"<?xml version="1.0" encoding="UTF-8"?>
 <xbel version="1.0"
       xmlns:bookmark="http://www.freedesktop.org/standards/desktop-bookmarks"
       xmlns:mime="http://www.freedesktop.org/standards/shared-mime-info"
 >
-  <bookmark href="trash:///A-File.txt" added="2020-09-24T20:00:00Z" modified="2020-09-25T20:00:00Z" visited="2020-09-25T20:00:00Z">
+  <bookmark href="file:///home/a/A-File.txt" added="2020-09-24T20:00:00Z" modified="2020-09-25T20:00:00Z" visited="2020-09-25T20:00:00Z">
     <info>
       <metadata owner="http://freedesktop.org">
         <mime:mime-type type="text/plain"/>
@@ -494,7 +872,7 @@ mod tests {
       </metadata>
     </info>
   </bookmark>
-  <bookmark href="mtp://phone_model/Path/To/File.txt" added="2020-09-24T20:00:00Z" modified="2020-09-25T20:00:00Z" visited="2020-09-25T20:00:00Z">
+  <bookmark href="file:///home/alice/A-File.txt" added="2020-09-24T20:00:00Z" modified="2020-09-25T20:00:00Z" visited="2020-09-25T20:00:00Z">
     <info>
       <metadata owner="http://freedesktop.org">
         <mime:mime-type type="text/plain"/>
@@ -507,46 +885,1688 @@ mod tests {
       </metadata>
     </info>
   </bookmark>
-  <bookmark href="ftp://user@host/Path/To/File" added="2021-09-14T18:00:00Z" modified="2021-09-14T18:00:00Z" visited="2021-09-14T18:00:00Z">
+  <bookmark href="file:///tmpfiles/A-File.txt" added="2020-09-24T20:00:00Z" modified="2020-09-25T20:00:00Z" visited="2020-09-25T20:00:00Z">
     <info>
       <metadata owner="http://freedesktop.org">
-        <mime:mime-type type="application/x-php"/>
+        <mime:mime-type type="text/plain"/>
         <bookmark:groups>
           <bookmark:group>gedit</bookmark:group>
         </bookmark:groups>
         <bookmark:applications>
-          <bookmark:application name="gedit" exec="&apos;gedit %u&apos;" modified="2021-09-14T18:00:00Z" count="1234"/>
+          <bookmark:application name="gedit" exec="&apos;gedit %u&apos;" modified="2020-09-25T20:00:00Z" count="1234"/>
         </bookmark:applications>
       </metadata>
     </info>
   </bookmark>
-  <bookmark href="sftp://user@host/Path/To/File" added="2021-09-14T18:00:00Z" modified="2021-09-14T18:00:00Z" visited="2021-09-14T18:00:00Z">
+  <bookmark href="file:///home/me/A-File.txt" added="2020-09-24T20:00:00Z" modified="2020-09-25T20:00:00Z" visited="2020-09-25T20:00:00Z">
     <info>
       <metadata owner="http://freedesktop.org">
-        <mime:mime-type type="application/x-php"/>
+        <mime:mime-type type="text/plain"/>
         <bookmark:groups>
           <bookmark:group>gedit</bookmark:group>
         </bookmark:groups>
         <bookmark:applications>
-          <bookmark:application name="gedit" exec="&apos;gedit %u&apos;" modified="2021-09-14T18:00:00Z" count="1234"/>
+          <bookmark:application name="gedit" exec="&apos;gedit %u&apos;" modified="2020-09-25T20:00:00Z" count="1234"/>
         </bookmark:applications>
       </metadata>
     </info>
   </bookmark>
-  <bookmark href="cdda://sr0/" added="2025-09-13T11:59:30.774505Z" modified="2025-09-13T11:59:30.774508Z" visited="2025-09-13T11:59:30.774506Z">
+</xbel>
+"#;
+        let mut output = Vec::new();
+        read_filter_write(
+            BufReader::new(input.as_bytes()),
+            &mut output,
+            &Filter::from(vec![
+                "raw:/home/a".parse().unwrap(),
+                "raw:/tmp".parse().unwrap(),
+            ]),
+        )
+        .unwrap();
+        let expected = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xbel version="1.0"
+      xmlns:bookmark="http://www.freedesktop.org/standards/desktop-bookmarks"
+      xmlns:mime="http://www.freedesktop.org/standards/shared-mime-info"
+>
+  <bookmark href="file:///home/me/A-File.txt" added="2020-09-24T20:00:00Z" modified="2020-09-25T20:00:00Z" visited="2020-09-25T20:00:00Z">
     <info>
       <metadata owner="http://freedesktop.org">
-        <mime:mime-type type="inode/directory"/>
+        <mime:mime-type type="text/plain"/>
+        <bookmark:groups>
+          <bookmark:group>gedit</bookmark:group>
+        </bookmark:groups>
         <bookmark:applications>
-          <bookmark:application name="org.gnome.Nautilus" exec="&apos;brasero %U&apos;" modified="2025-09-13T11:59:30.774507Z" count="1"/>
+          <bookmark:application name="gedit" exec="&apos;gedit %u&apos;" modified="2020-09-25T20:00:00Z" count="1234"/>
         </bookmark:applications>
       </metadata>
     </info>
   </bookmark>
 </xbel>
 "#;
-        let mut output = Vec::new();
-        read_filter_write(BufReader::new(input.as_bytes()), &mut output, &[]).unwrap();
-        assert_eq!(input, String::from_utf8(output).unwrap());
+        assert_eq!(expected, String::from_utf8(output).unwrap());
+    }
+
+    #[test]
+    fn filter_globs() {
+        let input = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xbel version="1.0"
+      xmlns:bookmark="http://www.freedesktop.org/standards/desktop-bookmarks"
+      xmlns:mime="http://www.freedesktop.org/standards/shared-mime-info"
+>
+  <bookmark href="file:///home/me/Downloads/A-File.pdf" added="2020-09-24T20:00:00Z" modified="2020-09-25T20:00:00Z" visited="2020-09-25T20:00:00Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="text/plain"/>
+        <bookmark:groups>
+          <bookmark:group>gedit</bookmark:group>
+        </bookmark:groups>
+        <bookmark:applications>
+          <bookmark:application name="gedit" exec="&apos;gedit %u&apos;" modified="2020-09-25T20:00:00Z" count="1234"/>
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>
+  <bookmark href="file:///opt/A%20Directory/Passwords.kdbx" added="2020-09-24T20:00:00Z" modified="2020-09-25T20:00:00Z" visited="2020-09-25T20:00:00Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="text/plain"/>
+        <bookmark:groups>
+          <bookmark:group>gedit</bookmark:group>
+        </bookmark:groups>
+        <bookmark:applications>
+          <bookmark:application name="gedit" exec="&apos;gedit %u&apos;" modified="2020-09-25T20:00:00Z" count="1234"/>
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>
+  <bookmark href="file:///tmp/A-File.txt" added="2020-09-24T20:00:00Z" modified="2020-09-25T20:00:00Z" visited="2020-09-25T20:00:00Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="text/plain"/>
+        <bookmark:groups>
+          <bookmark:group>gedit</bookmark:group>
+        </bookmark:groups>
+        <bookmark:applications>
+          <bookmark:application name="gedit" exec="&apos;gedit %u&apos;" modified="2020-09-25T20:00:00Z" count="1234"/>
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>
+  <bookmark href="file:///home/me/A-File.txt" added="2020-09-24T20:00:00Z" modified="2020-09-25T20:00:00Z" visited="2020-09-25T20:00:00Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="text/plain"/>
+        <bookmark:groups>
+          <bookmark:group>gedit</bookmark:group>
+        </bookmark:groups>
+        <bookmark:applications>
+          <bookmark:application name="gedit" exec="&apos;gedit %u&apos;" modified="2020-09-25T20:00:00Z" count="1234"/>
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>
+</xbel>
+"#;
+        let mut output = Vec::new();
+        read_filter_write(
+            BufReader::new(input.as_bytes()),
+            &mut output,
+            &Filter::from(vec![
+                "/tmp".parse().unwrap(),
+                "glob:*.kdbx".parse().unwrap(),
+                "glob:**/*.pdf".parse().unwrap(),
+            ]),
+        )
+        .unwrap();
+        let expected = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xbel version="1.0"
+      xmlns:bookmark="http://www.freedesktop.org/standards/desktop-bookmarks"
+      xmlns:mime="http://www.freedesktop.org/standards/shared-mime-info"
+>
+  <bookmark href="file:///home/me/A-File.txt" added="2020-09-24T20:00:00Z" modified="2020-09-25T20:00:00Z" visited="2020-09-25T20:00:00Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="text/plain"/>
+        <bookmark:groups>
+          <bookmark:group>gedit</bookmark:group>
+        </bookmark:groups>
+        <bookmark:applications>
+          <bookmark:application name="gedit" exec="&apos;gedit %u&apos;" modified="2020-09-25T20:00:00Z" count="1234"/>
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>
+</xbel>
+"#;
+        assert_eq!(expected, String::from_utf8(output).unwrap());
+    }
+
+    #[test]
+    fn filter_regex() {
+        let input = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xbel version="1.0"
+      xmlns:bookmark="http://www.freedesktop.org/standards/desktop-bookmarks"
+      xmlns:mime="http://www.freedesktop.org/standards/shared-mime-info"
+>
+  <bookmark href="sftp://user@prod-db/Path/To/File" added="2020-09-24T20:00:00Z" modified="2020-09-25T20:00:00Z" visited="2020-09-25T20:00:00Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="text/plain"/>
+        <bookmark:groups>
+          <bookmark:group>gedit</bookmark:group>
+        </bookmark:groups>
+        <bookmark:applications>
+          <bookmark:application name="gedit" exec="&apos;gedit %u&apos;" modified="2020-09-25T20:00:00Z" count="1234"/>
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>
+  <bookmark href="sftp://user@staging-db/Path/To/File" added="2020-09-24T20:00:00Z" modified="2020-09-25T20:00:00Z" visited="2020-09-25T20:00:00Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="text/plain"/>
+        <bookmark:groups>
+          <bookmark:group>gedit</bookmark:group>
+        </bookmark:groups>
+        <bookmark:applications>
+          <bookmark:application name="gedit" exec="&apos;gedit %u&apos;" modified="2020-09-25T20:00:00Z" count="1234"/>
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>
+  <bookmark href="trash:///A-File.txt" added="2020-09-24T20:00:00Z" modified="2020-09-25T20:00:00Z" visited="2020-09-25T20:00:00Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="text/plain"/>
+        <bookmark:groups>
+          <bookmark:group>gedit</bookmark:group>
+        </bookmark:groups>
+        <bookmark:applications>
+          <bookmark:application name="gedit" exec="&apos;gedit %u&apos;" modified="2020-09-25T20:00:00Z" count="1234"/>
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>
+  <bookmark href="file:///home/me/A-File.txt" added="2020-09-24T20:00:00Z" modified="2020-09-25T20:00:00Z" visited="2020-09-25T20:00:00Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="text/plain"/>
+        <bookmark:groups>
+          <bookmark:group>gedit</bookmark:group>
+        </bookmark:groups>
+        <bookmark:applications>
+          <bookmark:application name="gedit" exec="&apos;gedit %u&apos;" modified="2020-09-25T20:00:00Z" count="1234"/>
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>
+</xbel>
+"#;
+        let mut output = Vec::new();
+        read_filter_write(
+            BufReader::new(input.as_bytes()),
+            &mut output,
+            &Filter::from(vec!["regex:^sftp://.*@prod-".parse().unwrap()]),
+        )
+        .unwrap();
+        let expected = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xbel version="1.0"
+      xmlns:bookmark="http://www.freedesktop.org/standards/desktop-bookmarks"
+      xmlns:mime="http://www.freedesktop.org/standards/shared-mime-info"
+>
+  <bookmark href="sftp://user@staging-db/Path/To/File" added="2020-09-24T20:00:00Z" modified="2020-09-25T20:00:00Z" visited="2020-09-25T20:00:00Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="text/plain"/>
+        <bookmark:groups>
+          <bookmark:group>gedit</bookmark:group>
+        </bookmark:groups>
+        <bookmark:applications>
+          <bookmark:application name="gedit" exec="&apos;gedit %u&apos;" modified="2020-09-25T20:00:00Z" count="1234"/>
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>
+  <bookmark href="trash:///A-File.txt" added="2020-09-24T20:00:00Z" modified="2020-09-25T20:00:00Z" visited="2020-09-25T20:00:00Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="text/plain"/>
+        <bookmark:groups>
+          <bookmark:group>gedit</bookmark:group>
+        </bookmark:groups>
+        <bookmark:applications>
+          <bookmark:application name="gedit" exec="&apos;gedit %u&apos;" modified="2020-09-25T20:00:00Z" count="1234"/>
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>
+  <bookmark href="file:///home/me/A-File.txt" added="2020-09-24T20:00:00Z" modified="2020-09-25T20:00:00Z" visited="2020-09-25T20:00:00Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="text/plain"/>
+        <bookmark:groups>
+          <bookmark:group>gedit</bookmark:group>
+        </bookmark:groups>
+        <bookmark:applications>
+          <bookmark:application name="gedit" exec="&apos;gedit %u&apos;" modified="2020-09-25T20:00:00Z" count="1234"/>
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>
+</xbel>
+"#;
+        assert_eq!(expected, String::from_utf8(output).unwrap());
+    }
+
+    #[test]
+    fn filter_by_age() {
+        let input = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xbel version="1.0"
+      xmlns:bookmark="http://www.freedesktop.org/standards/desktop-bookmarks"
+      xmlns:mime="http://www.freedesktop.org/standards/shared-mime-info"
+>
+  <bookmark href="file:///opt/A%20Directory/A-File.txt" added="2022-04-08T20:00:00Z" modified="2022-04-08T20:00:00Z" visited="2022-04-08T20:00:00Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="text/plain"/>
+        <bookmark:groups>
+          <bookmark:group>gedit</bookmark:group>
+        </bookmark:groups>
+        <bookmark:applications>
+          <bookmark:application name="gedit" exec="&apos;gedit %u&apos;" modified="2022-04-08T20:00:00Z" count="1234"/>
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>
+  <bookmark href="file:///opt/Another%20Directory/Another-File.txt" added="2022-04-08T20:00:00Z" modified="22022-04-08T20:00:00Z" visited="2022-04-08T20:00:00Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="text/plain"/>
+        <bookmark:groups>
+          <bookmark:group>gedit</bookmark:group>
+        </bookmark:groups>
+        <bookmark:applications>
+          <bookmark:application name="gedit" exec="&apos;gedit %u&apos;" modified="22022-04-08T20:00:00Z" count="1234"/>
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>
+  <bookmark href="cdda://sr0/" added="2025-09-13T11:59:30.774505Z" modified="2025-09-13T11:59:30.774508Z" visited="2025-09-13T11:59:30.774506Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="inode/directory"/>
+        <bookmark:applications>
+          <bookmark:application name="org.gnome.Nautilus" exec="&apos;brasero %U&apos;" modified="2025-09-13T11:59:30.774508Z" count="1"/>
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>
+</xbel>
+"#;
+        let mut output = Vec::new();
+        read_filter_write(
+            BufReader::new(input.as_bytes()),
+            &mut output,
+            &Filter::from(vec!["modified-before:2025-01-01".parse().unwrap()]),
+        )
+        .unwrap();
+        let expected = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xbel version="1.0"
+      xmlns:bookmark="http://www.freedesktop.org/standards/desktop-bookmarks"
+      xmlns:mime="http://www.freedesktop.org/standards/shared-mime-info"
+>
+  <bookmark href="file:///opt/Another%20Directory/Another-File.txt" added="2022-04-08T20:00:00Z" modified="22022-04-08T20:00:00Z" visited="2022-04-08T20:00:00Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="text/plain"/>
+        <bookmark:groups>
+          <bookmark:group>gedit</bookmark:group>
+        </bookmark:groups>
+        <bookmark:applications>
+          <bookmark:application name="gedit" exec="&apos;gedit %u&apos;" modified="22022-04-08T20:00:00Z" count="1234"/>
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>
+  <bookmark href="cdda://sr0/" added="2025-09-13T11:59:30.774505Z" modified="2025-09-13T11:59:30.774508Z" visited="2025-09-13T11:59:30.774506Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="inode/directory"/>
+        <bookmark:applications>
+          <bookmark:application name="org.gnome.Nautilus" exec="&apos;brasero %U&apos;" modified="2025-09-13T11:59:30.774508Z" count="1"/>
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>
+</xbel>
+"#;
+        assert_eq!(expected, String::from_utf8(output).unwrap());
+    }
+
+    #[test]
+    fn filter_mime_types() {
+        let input = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xbel version="1.0"
+      xmlns:bookmark="http://www.freedesktop.org/standards/desktop-bookmarks"
+      xmlns:mime="http://www.freedesktop.org/standards/shared-mime-info"
+>
+  <bookmark href="file:///home/me/A-File.png" added="2020-09-24T20:00:00Z" modified="2020-09-25T20:00:00Z" visited="2020-09-25T20:00:00Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="image/png"/>
+        <bookmark:applications>
+          <bookmark:application name="org.gnome.Loupe" exec="&apos;loupe %u&apos;" modified="2020-09-25T20:00:00Z" count="1"/>
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>
+  <bookmark href="file:///home/me/A-File.pdf" added="2020-09-24T20:00:00Z" modified="2020-09-25T20:00:00Z" visited="2020-09-25T20:00:00Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="application/pdf"/>
+        <bookmark:applications>
+          <bookmark:application name="org.gnome.Papers" exec="&apos;papers %u&apos;" modified="2020-09-25T20:00:00Z" count="2"/>
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>
+  <bookmark href="file:///home/me/A-File.svg" added="2020-09-24T20:00:00Z" modified="2020-09-25T20:00:00Z" visited="2020-09-25T20:00:00Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="image/svg+xml"/>
+        <bookmark:applications>
+          <bookmark:application name="inkscape" exec="&apos;inkscape %u&apos;" modified="2020-09-25T20:00:00Z" count="3"/>
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>
+  <bookmark href="file:///home/me/A-File.txt" added="2020-09-24T20:00:00Z" modified="2020-09-25T20:00:00Z" visited="2020-09-25T20:00:00Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="text/plain"/>
+        <bookmark:groups>
+          <bookmark:group>gedit</bookmark:group>
+        </bookmark:groups>
+        <bookmark:applications>
+          <bookmark:application name="gedit" exec="&apos;gedit %u&apos;" modified="2020-09-25T20:00:00Z" count="1234"/>
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>
+</xbel>
+"#;
+        let mut output = Vec::new();
+        read_filter_write(
+            BufReader::new(input.as_bytes()),
+            &mut output,
+            &Filter::from(vec![
+                "mime:image/*".parse().unwrap(),
+                "mime:application/pdf".parse().unwrap(),
+            ]),
+        )
+        .unwrap();
+        let expected = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xbel version="1.0"
+      xmlns:bookmark="http://www.freedesktop.org/standards/desktop-bookmarks"
+      xmlns:mime="http://www.freedesktop.org/standards/shared-mime-info"
+>
+  <bookmark href="file:///home/me/A-File.txt" added="2020-09-24T20:00:00Z" modified="2020-09-25T20:00:00Z" visited="2020-09-25T20:00:00Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="text/plain"/>
+        <bookmark:groups>
+          <bookmark:group>gedit</bookmark:group>
+        </bookmark:groups>
+        <bookmark:applications>
+          <bookmark:application name="gedit" exec="&apos;gedit %u&apos;" modified="2020-09-25T20:00:00Z" count="1234"/>
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>
+</xbel>
+"#;
+        assert_eq!(expected, String::from_utf8(output).unwrap());
+    }
+
+    #[test]
+    fn filter_applications() {
+        let input = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xbel version="1.0"
+      xmlns:bookmark="http://www.freedesktop.org/standards/desktop-bookmarks"
+      xmlns:mime="http://www.freedesktop.org/standards/shared-mime-info"
+>
+  <bookmark href="file:///home/me/A-File.mp4" added="2020-09-24T20:00:00Z" modified="2020-09-25T20:00:00Z" visited="2020-09-25T20:00:00Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="video/mp4"/>
+        <bookmark:applications>
+          <bookmark:application name="org.gnome.Nautilus" exec="&apos;nautilus --new-window %u&apos;" modified="2020-09-25T20:00:00Z" count="1"/>
+          <bookmark:application name="vlc" exec="&apos;vlc %u&apos;" modified="2020-09-25T20:00:00Z" count="2"/>
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>
+  <bookmark href="file:///home/me/Another-File.mp4" added="2020-09-24T20:00:00Z" modified="2020-09-25T20:00:00Z" visited="2020-09-25T20:00:00Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="video/mp4"/>
+        <bookmark:applications>
+          <bookmark:application name="vlc" exec="&apos;vlc %u&apos;" modified="2020-09-25T20:00:00Z" count="2"/>
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>
+  <bookmark href="file:///home/me/Another-File.txt" added="2020-09-24T20:00:00Z" modified="2020-09-25T20:00:00Z" visited="2020-09-25T20:00:00Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="text/plain"/>
+        <bookmark:groups>
+          <bookmark:group>gedit</bookmark:group>
+        </bookmark:groups>
+        <bookmark:applications>
+          <bookmark:application name="gedit" exec="&apos;gedit %u&apos;" modified="2020-09-25T20:00:00Z" count="1234"/>
+          <bookmark:application name="org.gnome.Nautilus" exec="&apos;nautilus --new-window %u&apos;" modified="2020-09-25T20:00:00Z" count="1"/>
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>
+  <bookmark href="file:///home/me/A-File.txt" added="2020-09-24T20:00:00Z" modified="2020-09-25T20:00:00Z" visited="2020-09-25T20:00:00Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="text/plain"/>
+        <bookmark:groups>
+          <bookmark:group>gedit</bookmark:group>
+        </bookmark:groups>
+        <bookmark:applications>
+          <bookmark:application name="gedit" exec="&apos;gedit %u&apos;" modified="2020-09-25T20:00:00Z" count="1234"/>
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>
+</xbel>
+"#;
+        let mut output = Vec::new();
+        read_filter_write(
+            BufReader::new(input.as_bytes()),
+            &mut output,
+            &Filter::from(vec!["app:org.gnome.Nautilus".parse().unwrap()]),
+        )
+        .unwrap();
+        let expected = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xbel version="1.0"
+      xmlns:bookmark="http://www.freedesktop.org/standards/desktop-bookmarks"
+      xmlns:mime="http://www.freedesktop.org/standards/shared-mime-info"
+>
+  <bookmark href="file:///home/me/Another-File.mp4" added="2020-09-24T20:00:00Z" modified="2020-09-25T20:00:00Z" visited="2020-09-25T20:00:00Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="video/mp4"/>
+        <bookmark:applications>
+          <bookmark:application name="vlc" exec="&apos;vlc %u&apos;" modified="2020-09-25T20:00:00Z" count="2"/>
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>
+  <bookmark href="file:///home/me/A-File.txt" added="2020-09-24T20:00:00Z" modified="2020-09-25T20:00:00Z" visited="2020-09-25T20:00:00Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="text/plain"/>
+        <bookmark:groups>
+          <bookmark:group>gedit</bookmark:group>
+        </bookmark:groups>
+        <bookmark:applications>
+          <bookmark:application name="gedit" exec="&apos;gedit %u&apos;" modified="2020-09-25T20:00:00Z" count="1234"/>
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>
+</xbel>
+"#;
+        assert_eq!(expected, String::from_utf8(output).unwrap());
+    }
+
+    #[test]
+    fn filter_groups() {
+        let input = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xbel version="1.0"
+      xmlns:bookmark="http://www.freedesktop.org/standards/desktop-bookmarks"
+      xmlns:mime="http://www.freedesktop.org/standards/shared-mime-info"
+>
+  <bookmark href="file:///home/me/A-File.txt" added="2020-09-24T20:00:00Z" modified="2020-09-25T20:00:00Z" visited="2020-09-25T20:00:00Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="text/plain"/>
+        <bookmark:groups>
+          <bookmark:group>gedit</bookmark:group>
+        </bookmark:groups>
+        <bookmark:applications>
+          <bookmark:application name="gedit" exec="&apos;gedit %u&apos;" modified="2020-09-25T20:00:00Z" count="1234"/>
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>
+  <bookmark href="file:///home/me/A-File.rs" added="2020-09-24T20:00:00Z" modified="2020-09-25T20:00:00Z" visited="2020-09-25T20:00:00Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="text/plain"/>
+        <bookmark:groups>
+          <bookmark:group>gedit</bookmark:group>
+          <bookmark:group>Rust</bookmark:group>
+        </bookmark:groups>
+        <bookmark:applications>
+          <bookmark:application name="gedit" exec="&apos;gedit %u&apos;" modified="2020-09-25T20:00:00Z" count="1234"/>
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>
+  <bookmark href="file:///home/me/A-File.md" added="2020-09-24T20:00:00Z" modified="2020-09-25T20:00:00Z" visited="2020-09-25T20:00:00Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="text/plain"/>
+        <bookmark:groups>
+          <bookmark:group>org.gnome.TextEditor</bookmark:group>
+        </bookmark:groups>
+        <bookmark:applications>
+          <bookmark:application name="org.gnome.TextEditor" exec="&apos;gnome-text-editor %U&apos;" modified="2020-09-25T20:00:00Z" count="3"/>
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>
+  <bookmark href="file:///home/me/A-Directory" added="2020-09-24T20:00:00Z" modified="2020-09-25T20:00:00Z" visited="2020-09-25T20:00:00Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="inode/directory"/>
+        <bookmark:applications>
+          <bookmark:application name="org.gnome.Nautilus" exec="&apos;nautilus --new-window %u&apos;" modified="2020-09-25T20:00:00Z" count="1"/>
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>
+</xbel>
+"#;
+        let mut output = Vec::new();
+        read_filter_write(
+            BufReader::new(input.as_bytes()),
+            &mut output,
+            &Filter::from(vec!["group:gedit".parse().unwrap()]),
+        )
+        .unwrap();
+        let expected = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xbel version="1.0"
+      xmlns:bookmark="http://www.freedesktop.org/standards/desktop-bookmarks"
+      xmlns:mime="http://www.freedesktop.org/standards/shared-mime-info"
+>
+  <bookmark href="file:///home/me/A-File.md" added="2020-09-24T20:00:00Z" modified="2020-09-25T20:00:00Z" visited="2020-09-25T20:00:00Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="text/plain"/>
+        <bookmark:groups>
+          <bookmark:group>org.gnome.TextEditor</bookmark:group>
+        </bookmark:groups>
+        <bookmark:applications>
+          <bookmark:application name="org.gnome.TextEditor" exec="&apos;gnome-text-editor %U&apos;" modified="2020-09-25T20:00:00Z" count="3"/>
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>
+  <bookmark href="file:///home/me/A-Directory" added="2020-09-24T20:00:00Z" modified="2020-09-25T20:00:00Z" visited="2020-09-25T20:00:00Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="inode/directory"/>
+        <bookmark:applications>
+          <bookmark:application name="org.gnome.Nautilus" exec="&apos;nautilus --new-window %u&apos;" modified="2020-09-25T20:00:00Z" count="1"/>
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>
+</xbel>
+"#;
+        assert_eq!(expected, String::from_utf8(output).unwrap());
+    }
+
+    #[test]
+    fn filter_keep() {
+        let input = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xbel version="1.0"
+      xmlns:bookmark="http://www.freedesktop.org/standards/desktop-bookmarks"
+      xmlns:mime="http://www.freedesktop.org/standards/shared-mime-info"
+>
+  <bookmark href="file:///home/me/work/A-File.txt" added="2020-09-24T20:00:00Z" modified="2020-09-25T20:00:00Z" visited="2020-09-25T20:00:00Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="text/plain"/>
+        <bookmark:groups>
+          <bookmark:group>gedit</bookmark:group>
+        </bookmark:groups>
+        <bookmark:applications>
+          <bookmark:application name="gedit" exec="&apos;gedit %u&apos;" modified="2020-09-25T20:00:00Z" count="1234"/>
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>
+  <bookmark href="file:///home/me/work/shared/A-File.txt" added="2020-09-24T20:00:00Z" modified="2020-09-25T20:00:00Z" visited="2020-09-25T20:00:00Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="text/plain"/>
+        <bookmark:groups>
+          <bookmark:group>gedit</bookmark:group>
+        </bookmark:groups>
+        <bookmark:applications>
+          <bookmark:application name="gedit" exec="&apos;gedit %u&apos;" modified="2020-09-25T20:00:00Z" count="1234"/>
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>
+  <bookmark href="file:///home/me/work/shared/tmp/A-File.txt" added="2020-09-24T20:00:00Z" modified="2020-09-25T20:00:00Z" visited="2020-09-25T20:00:00Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="text/plain"/>
+        <bookmark:groups>
+          <bookmark:group>gedit</bookmark:group>
+        </bookmark:groups>
+        <bookmark:applications>
+          <bookmark:application name="gedit" exec="&apos;gedit %u&apos;" modified="2020-09-25T20:00:00Z" count="1234"/>
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>
+  <bookmark href="file:///home/me/A-File.txt" added="2020-09-24T20:00:00Z" modified="2020-09-25T20:00:00Z" visited="2020-09-25T20:00:00Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="text/plain"/>
+        <bookmark:groups>
+          <bookmark:group>gedit</bookmark:group>
+        </bookmark:groups>
+        <bookmark:applications>
+          <bookmark:application name="gedit" exec="&apos;gedit %u&apos;" modified="2020-09-25T20:00:00Z" count="1234"/>
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>
+</xbel>
+"#;
+        let mut output = Vec::new();
+        read_filter_write(
+            BufReader::new(input.as_bytes()),
+            &mut output,
+            &Filter::from(vec![
+                "/home/me/work".parse().unwrap(),
+                "keep:/home/me/work/shared".parse().unwrap(),
+                "/home/me/work/shared/tmp".parse().unwrap(),
+            ]),
+        )
+        .unwrap();
+        let expected = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xbel version="1.0"
+      xmlns:bookmark="http://www.freedesktop.org/standards/desktop-bookmarks"
+      xmlns:mime="http://www.freedesktop.org/standards/shared-mime-info"
+>
+  <bookmark href="file:///home/me/work/shared/A-File.txt" added="2020-09-24T20:00:00Z" modified="2020-09-25T20:00:00Z" visited="2020-09-25T20:00:00Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="text/plain"/>
+        <bookmark:groups>
+          <bookmark:group>gedit</bookmark:group>
+        </bookmark:groups>
+        <bookmark:applications>
+          <bookmark:application name="gedit" exec="&apos;gedit %u&apos;" modified="2020-09-25T20:00:00Z" count="1234"/>
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>
+  <bookmark href="file:///home/me/A-File.txt" added="2020-09-24T20:00:00Z" modified="2020-09-25T20:00:00Z" visited="2020-09-25T20:00:00Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="text/plain"/>
+        <bookmark:groups>
+          <bookmark:group>gedit</bookmark:group>
+        </bookmark:groups>
+        <bookmark:applications>
+          <bookmark:application name="gedit" exec="&apos;gedit %u&apos;" modified="2020-09-25T20:00:00Z" count="1234"/>
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>
+</xbel>
+"#;
+        assert_eq!(expected, String::from_utf8(output).unwrap());
+    }
+
+    #[test]
+    fn prune_applications() {
+        let input = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xbel version="1.0"
+      xmlns:bookmark="http://www.freedesktop.org/standards/desktop-bookmarks"
+      xmlns:mime="http://www.freedesktop.org/standards/shared-mime-info"
+>
+  <bookmark href="file:///home/me/A-File.mp4" added="2020-09-24T20:00:00Z" modified="2020-09-25T20:00:00Z" visited="2020-09-25T20:00:00Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="video/mp4"/>
+        <bookmark:applications>
+          <bookmark:application name="vlc" exec="&apos;vlc %u&apos;" modified="2020-09-24T20:00:00Z" count="5"/>
+          <bookmark:application name="org.gnome.Nautilus" exec="&apos;nautilus --new-window %u&apos;" modified="2020-09-25T20:00:00Z" count="1"/>
+          <bookmark:application name="vlc" exec="&apos;vlc %u&apos;" modified="2020-09-25T20:00:00Z" count="2"/>
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>
+  <bookmark href="file:///home/me/Another-File.mp4" added="2020-09-24T20:00:00Z" modified="2020-09-25T20:00:00Z" visited="2020-09-25T20:00:00Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="video/mp4"/>
+        <bookmark:applications>
+          <bookmark:application name="vlc" exec="&apos;vlc %u&apos;" modified="2020-09-25T20:00:00Z" count="2"/>
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>
+  <bookmark href="file:///home/me/A-File.txt" added="2020-09-24T20:00:00Z" modified="2020-09-25T20:00:00Z" visited="2020-09-25T20:00:00Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="text/plain"/>
+        <bookmark:groups>
+          <bookmark:group>gedit</bookmark:group>
+        </bookmark:groups>
+        <bookmark:applications>
+          <bookmark:application name="gedit" exec="&apos;gedit %u&apos;" modified="2020-09-25T20:00:00Z" count="1234"/>
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>
+  <bookmark href="file:///home/me/Another-File.txt" added="2020-09-24T20:00:00Z" modified="2020-09-25T20:00:00Z" visited="2020-09-25T20:00:00Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="text/plain"/>
+        <bookmark:groups>
+          <bookmark:group>gedit</bookmark:group>
+        </bookmark:groups>
+        <bookmark:applications>
+          <bookmark:application name="gedit" exec="&apos;gedit %u&apos;" modified="2020-09-25T20:00:00Z" count="1234"/>
+          <bookmark:application name="org.gnome.Nautilus" exec="&apos;nautilus --new-window %u&apos;" modified="2020-09-25T20:00:00Z" count="1"/>
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>
+</xbel>
+"#;
+        let mut output = Vec::new();
+        read_filter_write(
+            BufReader::new(input.as_bytes()),
+            &mut output,
+            &Filter {
+                prune_applications: vec![String::from("vlc"), String::from("gedit")],
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let expected = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xbel version="1.0"
+      xmlns:bookmark="http://www.freedesktop.org/standards/desktop-bookmarks"
+      xmlns:mime="http://www.freedesktop.org/standards/shared-mime-info"
+>
+  <bookmark href="file:///home/me/A-File.mp4" added="2020-09-24T20:00:00Z" modified="2020-09-25T20:00:00Z" visited="2020-09-25T20:00:00Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="video/mp4"/>
+        <bookmark:applications>
+          <bookmark:application name="org.gnome.Nautilus" exec="&apos;nautilus --new-window %u&apos;" modified="2020-09-25T20:00:00Z" count="1"/>
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>
+  <bookmark href="file:///home/me/Another-File.txt" added="2020-09-24T20:00:00Z" modified="2020-09-25T20:00:00Z" visited="2020-09-25T20:00:00Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="text/plain"/>
+        <bookmark:groups>
+          <bookmark:group>gedit</bookmark:group>
+        </bookmark:groups>
+        <bookmark:applications>
+          <bookmark:application name="org.gnome.Nautilus" exec="&apos;nautilus --new-window %u&apos;" modified="2020-09-25T20:00:00Z" count="1"/>
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>
+</xbel>
+"#;
+        assert_eq!(expected, String::from_utf8(output).unwrap());
+    }
+
+    #[test]
+    fn filter_max_entries() {
+        let input = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xbel version="1.0"
+      xmlns:bookmark="http://www.freedesktop.org/standards/desktop-bookmarks"
+      xmlns:mime="http://www.freedesktop.org/standards/shared-mime-info"
+>
+  <bookmark href="file:///home/me/A-File.txt" added="2020-09-24T20:00:00Z" modified="2020-09-25T20:00:00Z" visited="2020-09-25T20:00:00Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="text/plain"/>
+        <bookmark:groups>
+          <bookmark:group>gedit</bookmark:group>
+        </bookmark:groups>
+        <bookmark:applications>
+          <bookmark:application name="gedit" exec="&apos;gedit %u&apos;" modified="2020-09-25T20:00:00Z" count="1234"/>
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>
+  <bookmark href="file:///home/me/B-File.txt" added="2020-09-24T20:00:00Z" modified="2020-09-24T20:00:00Z" visited="2021-09-25T20:00:00Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="text/plain"/>
+        <bookmark:groups>
+          <bookmark:group>gedit</bookmark:group>
+        </bookmark:groups>
+        <bookmark:applications>
+          <bookmark:application name="gedit" exec="&apos;gedit %u&apos;" modified="2020-09-24T20:00:00Z" count="1234"/>
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>
+  <bookmark href="file:///tmp/C-File.txt" added="2020-09-24T20:00:00Z" modified="2020-09-25T20:00:00Z" visited="2025-09-25T20:00:00Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="text/plain"/>
+        <bookmark:groups>
+          <bookmark:group>gedit</bookmark:group>
+        </bookmark:groups>
+        <bookmark:applications>
+          <bookmark:application name="gedit" exec="&apos;gedit %u&apos;" modified="2020-09-25T20:00:00Z" count="1234"/>
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>
+  <bookmark href="file:///home/me/D-File.txt" added="2020-09-24T20:00:00Z" modified="2020-09-25T20:00:00Z" visited="22022-09-25T20:00:00Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="text/plain"/>
+        <bookmark:groups>
+          <bookmark:group>gedit</bookmark:group>
+        </bookmark:groups>
+        <bookmark:applications>
+          <bookmark:application name="gedit" exec="&apos;gedit %u&apos;" modified="2020-09-25T20:00:00Z" count="1234"/>
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>
+  <bookmark href="file:///home/me/E-File.txt" added="2020-09-24T20:00:00Z" modified="2022-09-25T20:00:00Z" visited="2022-09-25T20:00:00Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="text/plain"/>
+        <bookmark:groups>
+          <bookmark:group>gedit</bookmark:group>
+        </bookmark:groups>
+        <bookmark:applications>
+          <bookmark:application name="gedit" exec="&apos;gedit %u&apos;" modified="2022-09-25T20:00:00Z" count="1234"/>
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>
+  <bookmark href="file:///home/me/F-File.txt" added="2020-09-24T20:00:00Z" modified="2020-09-26T20:00:00Z" visited="2020-09-26T20:00:00Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="text/plain"/>
+        <bookmark:groups>
+          <bookmark:group>gedit</bookmark:group>
+        </bookmark:groups>
+        <bookmark:applications>
+          <bookmark:application name="gedit" exec="&apos;gedit %u&apos;" modified="2020-09-26T20:00:00Z" count="1234"/>
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>
+</xbel>
+"#;
+        let mut output = Vec::new();
+        read_filter_write(
+            BufReader::new(input.as_bytes()),
+            &mut output,
+            &Filter {
+                max_entries: Some(2),
+                ..Filter::from(vec!["/tmp".parse().unwrap()])
+            },
+        )
+        .unwrap();
+        let expected = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xbel version="1.0"
+      xmlns:bookmark="http://www.freedesktop.org/standards/desktop-bookmarks"
+      xmlns:mime="http://www.freedesktop.org/standards/shared-mime-info"
+>
+  <bookmark href="file:///home/me/B-File.txt" added="2020-09-24T20:00:00Z" modified="2020-09-24T20:00:00Z" visited="2021-09-25T20:00:00Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="text/plain"/>
+        <bookmark:groups>
+          <bookmark:group>gedit</bookmark:group>
+        </bookmark:groups>
+        <bookmark:applications>
+          <bookmark:application name="gedit" exec="&apos;gedit %u&apos;" modified="2020-09-24T20:00:00Z" count="1234"/>
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>
+  <bookmark href="file:///home/me/E-File.txt" added="2020-09-24T20:00:00Z" modified="2022-09-25T20:00:00Z" visited="2022-09-25T20:00:00Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="text/plain"/>
+        <bookmark:groups>
+          <bookmark:group>gedit</bookmark:group>
+        </bookmark:groups>
+        <bookmark:applications>
+          <bookmark:application name="gedit" exec="&apos;gedit %u&apos;" modified="2022-09-25T20:00:00Z" count="1234"/>
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>
+</xbel>
+"#;
+        assert_eq!(expected, String::from_utf8(output).unwrap());
+    }
+
+    #[test]
+    fn filter_max_entries_by_modified() {
+        let input = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xbel version="1.0"
+      xmlns:bookmark="http://www.freedesktop.org/standards/desktop-bookmarks"
+      xmlns:mime="http://www.freedesktop.org/standards/shared-mime-info"
+>
+  <bookmark href="file:///home/me/A-File.txt" added="2020-09-24T20:00:00Z" modified="2020-09-25T20:00:00Z" visited="2020-09-25T20:00:00Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="text/plain"/>
+        <bookmark:groups>
+          <bookmark:group>gedit</bookmark:group>
+        </bookmark:groups>
+        <bookmark:applications>
+          <bookmark:application name="gedit" exec="&apos;gedit %u&apos;" modified="2020-09-25T20:00:00Z" count="1234"/>
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>
+  <bookmark href="file:///home/me/B-File.txt" added="2020-09-24T20:00:00Z" modified="2020-09-24T20:00:00Z" visited="2021-09-25T20:00:00Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="text/plain"/>
+        <bookmark:groups>
+          <bookmark:group>gedit</bookmark:group>
+        </bookmark:groups>
+        <bookmark:applications>
+          <bookmark:application name="gedit" exec="&apos;gedit %u&apos;" modified="2020-09-24T20:00:00Z" count="1234"/>
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>
+  <bookmark href="file:///tmp/C-File.txt" added="2020-09-24T20:00:00Z" modified="2020-09-25T20:00:00Z" visited="2025-09-25T20:00:00Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="text/plain"/>
+        <bookmark:groups>
+          <bookmark:group>gedit</bookmark:group>
+        </bookmark:groups>
+        <bookmark:applications>
+          <bookmark:application name="gedit" exec="&apos;gedit %u&apos;" modified="2020-09-25T20:00:00Z" count="1234"/>
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>
+  <bookmark href="file:///home/me/D-File.txt" added="2020-09-24T20:00:00Z" modified="2020-09-25T20:00:00Z" visited="22022-09-25T20:00:00Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="text/plain"/>
+        <bookmark:groups>
+          <bookmark:group>gedit</bookmark:group>
+        </bookmark:groups>
+        <bookmark:applications>
+          <bookmark:application name="gedit" exec="&apos;gedit %u&apos;" modified="2020-09-25T20:00:00Z" count="1234"/>
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>
+  <bookmark href="file:///home/me/E-File.txt" added="2020-09-24T20:00:00Z" modified="2022-09-25T20:00:00Z" visited="2022-09-25T20:00:00Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="text/plain"/>
+        <bookmark:groups>
+          <bookmark:group>gedit</bookmark:group>
+        </bookmark:groups>
+        <bookmark:applications>
+          <bookmark:application name="gedit" exec="&apos;gedit %u&apos;" modified="2022-09-25T20:00:00Z" count="1234"/>
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>
+  <bookmark href="file:///home/me/F-File.txt" added="2020-09-24T20:00:00Z" modified="2020-09-26T20:00:00Z" visited="2020-09-26T20:00:00Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="text/plain"/>
+        <bookmark:groups>
+          <bookmark:group>gedit</bookmark:group>
+        </bookmark:groups>
+        <bookmark:applications>
+          <bookmark:application name="gedit" exec="&apos;gedit %u&apos;" modified="2020-09-26T20:00:00Z" count="1234"/>
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>
+</xbel>
+"#;
+        let mut output = Vec::new();
+        read_filter_write(
+            BufReader::new(input.as_bytes()),
+            &mut output,
+            &Filter {
+                max_entries: Some(3),
+                max_entries_by: Timestamp::Modified,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let expected = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xbel version="1.0"
+      xmlns:bookmark="http://www.freedesktop.org/standards/desktop-bookmarks"
+      xmlns:mime="http://www.freedesktop.org/standards/shared-mime-info"
+>
+  <bookmark href="file:///home/me/A-File.txt" added="2020-09-24T20:00:00Z" modified="2020-09-25T20:00:00Z" visited="2020-09-25T20:00:00Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="text/plain"/>
+        <bookmark:groups>
+          <bookmark:group>gedit</bookmark:group>
+        </bookmark:groups>
+        <bookmark:applications>
+          <bookmark:application name="gedit" exec="&apos;gedit %u&apos;" modified="2020-09-25T20:00:00Z" count="1234"/>
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>
+  <bookmark href="file:///home/me/E-File.txt" added="2020-09-24T20:00:00Z" modified="2022-09-25T20:00:00Z" visited="2022-09-25T20:00:00Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="text/plain"/>
+        <bookmark:groups>
+          <bookmark:group>gedit</bookmark:group>
+        </bookmark:groups>
+        <bookmark:applications>
+          <bookmark:application name="gedit" exec="&apos;gedit %u&apos;" modified="2022-09-25T20:00:00Z" count="1234"/>
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>
+  <bookmark href="file:///home/me/F-File.txt" added="2020-09-24T20:00:00Z" modified="2020-09-26T20:00:00Z" visited="2020-09-26T20:00:00Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="text/plain"/>
+        <bookmark:groups>
+          <bookmark:group>gedit</bookmark:group>
+        </bookmark:groups>
+        <bookmark:applications>
+          <bookmark:application name="gedit" exec="&apos;gedit %u&apos;" modified="2020-09-26T20:00:00Z" count="1234"/>
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>
+</xbel>
+"#;
+        assert_eq!(expected, String::from_utf8(output).unwrap());
+    }
+
+    #[test]
+    fn output_files() {
+        let dir =
+            env::temp_dir().join(format!("clean-recently-used-test-output-{}", process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("recently-used.xbel");
+        fs::write(&file, "").unwrap();
+        let link = dir.join("recently-used.xbel.bak");
+        hard_link(&file, &link).unwrap();
+        assert!(same_file(&file, &dir.join(".").join("recently-used.xbel")));
+        assert!(same_file(&file, &link));
+        assert!(!same_file(&file, &dir));
+        assert!(!same_file(&file, &dir.join("out.xbel")));
+        assert_eq!(link, with_suffix(&file, ".bak"));
+
+        let output = dir.join("out.xbel");
+        let temporary = dir.join("out.xbel-temporary");
+        let written = write_output(&temporary, |output| Ok(output.write_all(b"<xbel/>")?));
+        assert!(written.is_ok());
+        assert_eq!(b"<xbel/>", &fs::read(&temporary).unwrap()[..]);
+        assert!(!output.exists());
+        fs::remove_file(&temporary).unwrap();
+        let failed = write_output(&temporary, |output| {
+            read_filter_write("<xbel></xbl>".as_bytes(), output, &Filter::default())
+        });
+        assert!(matches!(failed, Err(XbelError::Xml { position: 6, .. })));
+        assert!(!temporary.exists());
+        assert!(!output.exists());
+
+        fs::write(&temporary, "<xbel/>").unwrap();
+        fs::remove_file(&link).unwrap();
+        fs::create_dir(&link).unwrap();
+        assert!(matches!(
+            replace(&file, &temporary, true),
+            Err(XbelError::Io { path: Some(path), .. }) if path == link
+        ));
+        assert!(!temporary.exists());
+        assert_eq!(b"", &fs::read(&file).unwrap()[..]);
+        fs::remove_dir(&link).unwrap();
+        fs::write(&temporary, "<xbel/>").unwrap();
+        replace(&file, &temporary, true).unwrap();
+        assert!(!temporary.exists());
+        assert_eq!(b"<xbel/>", &fs::read(&file).unwrap()[..]);
+        assert_eq!(b"", &fs::read(&link).unwrap()[..]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    fn clean_args(args: &[&str]) -> CleanArgs {
+        let args = cli::with_default_command(args.iter().map(|arg| arg.into()));
+        let Command::Clean(args) = Cli::try_parse_from(args).unwrap().command else {
+            panic!("not the clean command");
+        };
+        args
+    }
+
+    #[test]
+    fn stdout_conflicts() {
+        for (args, output, conflict) in [
+            (&["clean-recently-used", "/tmp"][..], "in.xbel", false),
+            (
+                &["clean-recently-used", "--report=-", "/tmp"],
+                "in.xbel",
+                false,
+            ),
+            (&["clean-recently-used", "/tmp"], "-", false),
+            (&["clean-recently-used", "--report=-", "/tmp"], "-", true),
+            (
+                &["clean-recently-used", "-n", "--report=-", "/tmp"],
+                "-",
+                false,
+            ),
+            (
+                &["clean-recently-used", "--report=r.json", "/tmp"],
+                "-",
+                false,
+            ),
+            (&["clean-recently-used", "--diff", "/tmp"], "in.xbel", false),
+            (&["clean-recently-used", "--diff", "/tmp"], "-", true),
+            (&["clean-recently-used", "-n", "--diff", "/tmp"], "-", false),
+            (
+                &["clean-recently-used", "-n", "--diff", "--report=-", "/tmp"],
+                "in.xbel",
+                true,
+            ),
+        ] {
+            assert_eq!(
+                conflict,
+                check_stdout(&clean_args(args), Path::new(output)).is_err(),
+                "{args:?} {output}"
+            );
+        }
+    }
+
+    #[test]
+    fn summary() {
+        let input = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xbel version="1.0"
+      xmlns:bookmark="http://www.freedesktop.org/standards/desktop-bookmarks"
+      xmlns:mime="http://www.freedesktop.org/standards/shared-mime-info"
+>
+  <bookmark href="file:///tmp/A%20File.txt" added="2020-09-24T20:00:00Z" modified="2020-09-25T20:00:00Z" visited="2020-09-25T20:00:00Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="text/plain"/>
+        <bookmark:groups>
+          <bookmark:group>gedit</bookmark:group>
+        </bookmark:groups>
+        <bookmark:applications>
+          <bookmark:application name="gedit" exec="&apos;gedit %u&apos;" modified="2020-09-25T20:00:00Z" count="1234"/>
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>
+  <bookmark href="sftp://user@prod-db/Path/To/File" added="2020-09-24T20:00:00Z" modified="2020-09-25T20:00:00Z" visited="2020-09-25T20:00:00Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="text/plain"/>
+        <bookmark:groups>
+          <bookmark:group>gedit</bookmark:group>
+        </bookmark:groups>
+        <bookmark:applications>
+          <bookmark:application name="gedit" exec="&apos;gedit %u&apos;" modified="2020-09-25T20:00:00Z" count="1234"/>
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>
+  <bookmark href="file:///home/me/A-File.mp4" added="2020-09-24T20:00:00Z" modified="2020-09-25T20:00:00Z" visited="2020-09-25T20:00:00Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="video/mp4"/>
+        <bookmark:applications>
+          <bookmark:application name="vlc" exec="&apos;vlc %u&apos;" modified="2020-09-25T20:00:00Z" count="2"/>
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>
+  <bookmark href="file:///home/me/A-File.txt" added="2020-09-24T20:00:00Z" modified="2020-09-25T20:00:00Z" visited="2020-09-25T20:00:00Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="text/plain"/>
+        <bookmark:groups>
+          <bookmark:group>gedit</bookmark:group>
+        </bookmark:groups>
+        <bookmark:applications>
+          <bookmark:application name="gedit" exec="&apos;gedit %u&apos;" modified="2020-09-25T20:00:00Z" count="1234"/>
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>
+  <bookmark href="file:///home/me/B-File.txt" added="2020-09-24T20:00:00Z" modified="2020-09-25T20:00:00Z" visited="2021-09-25T20:00:00Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="text/plain"/>
+        <bookmark:groups>
+          <bookmark:group>gedit</bookmark:group>
+        </bookmark:groups>
+        <bookmark:applications>
+          <bookmark:application name="gedit" exec="&apos;gedit %u&apos;" modified="2020-09-25T20:00:00Z" count="1234"/>
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>
+</xbel>
+"#;
+        let summary = read_filter_write(
+            BufReader::new(input.as_bytes()),
+            io::sink(),
+            &Filter {
+                prune_applications: vec![String::from("vlc")],
+                max_entries: Some(1),
+                ..Filter::from(vec![
+                    "/tmp".parse().unwrap(),
+                    "regex:@prod-".parse().unwrap(),
+                ])
+            },
+        )
+        .unwrap();
+        let expected = Summary {
+            kept: 1,
+            removed: vec![
+                Removal {
+                    href: String::from("file:///tmp/A%20File.txt"),
+                    path: Some(String::from("/tmp/A File.txt")),
+                    reason: Reason::Rule(String::from("/tmp")),
+                },
+                Removal {
+                    href: String::from("sftp://user@prod-db/Path/To/File"),
+                    path: None,
+                    reason: Reason::Rule(String::from("regex:@prod-")),
+                },
+                Removal {
+                    href: String::from("file:///home/me/A-File.mp4"),
+                    path: Some(String::from("/home/me/A-File.mp4")),
+                    reason: Reason::NoApplications,
+                },
+                Removal {
+                    href: String::from("file:///home/me/A-File.txt"),
+                    path: Some(String::from("/home/me/A-File.txt")),
+                    reason: Reason::MaxEntries,
+                },
+            ],
+        };
+        assert_eq!(expected, summary);
+
+        let mut output = Vec::new();
+        report::print_dry_run(&summary, &mut output).unwrap();
+        let expected = "file:///tmp/A%20File.txt (/tmp/A File.txt): rule /tmp
+sftp://user@prod-db/Path/To/File: rule regex:@prod-
+file:///home/me/A-File.mp4 (/home/me/A-File.mp4): no applications left after pruning
+file:///home/me/A-File.txt (/home/me/A-File.txt): not among the newest entries
+Would remove 4 entries and keep 1.
+";
+        assert_eq!(expected, String::from_utf8(output).unwrap());
+    }
+
+    #[test]
+    fn invalid_xml() {
+        let input = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xbel version="1.0">
+  <bookmark href="file:///tmp/A-File.txt" added="2022-04-08T20:00:00Z" modified="2022-04-08T20:00:00Z" visited="2022-04-08T20:00:00Z">
+  </bookmark>
+</xbl>
+"#;
+        let mut output = Vec::new();
+        let error = read_filter_write(input.as_bytes(), &mut output, &Filter::default())
+            .unwrap_err()
+            .to_string();
+        assert!(error.starts_with("XmlError at byte 209: "), "{error}");
+
+        let input = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xbel version="1.0">
+  <bookmark href="gopher://example.com/A-File.txt" added="2022-04-08T20:00:00Z" modified="2022-04-08T20:00:00Z" visited="2022-04-08T20:00:00Z">
+  </bookmark>
+</xbel>
+"#;
+        let error = read_filter_write(input.as_bytes(), &mut output, &Filter::default())
+            .unwrap_err()
+            .to_string();
+        assert_eq!(
+            "HrefNotRecognizedError at byte 62: gopher://example.com/A-File.txt",
+            error
+        );
+    }
+
+    #[test]
+    fn pass_through_other_events() {
+        let input = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE xbel PUBLIC "+//IDN python.org//DTD XML Bookmark Exchange Language 1.0//EN//XML" "http://pyxml.sourceforge.net/topics/dtds/xbel-1.0.dtd">
+<?xml-stylesheet type="text/css" href="xbel.css"?>
+<!-- edited by hand -->
+<xbel version="1.0"
+      xmlns:bookmark="http://www.freedesktop.org/standards/desktop-bookmarks"
+      xmlns:mime="http://www.freedesktop.org/standards/shared-mime-info"
+>
+  <title>Recently &amp; Frequently Used &#x1F4DD;</title>
+  <desc><![CDATA[Files <opened> recently]]></desc>
+  <!-- kept -->
+  <bookmark href="file:///home/me/A-File.txt" added="2020-09-24T20:00:00Z" modified="2020-09-25T20:00:00Z" visited="2020-09-25T20:00:00Z">
+    <!-- a comment inside a kept bookmark -->
+    <info>
+      <?processing instruction?>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="text/plain"/>
+        <bookmark:groups>
+          <bookmark:group><![CDATA[Text & Code]]></bookmark:group>
+        </bookmark:groups>
+      </metadata>
+    </info>
+  </bookmark>
+  <!-- removed -->
+  <bookmark href="file:///tmp/A-File.txt" added="2020-09-24T20:00:00Z" modified="2020-09-25T20:00:00Z" visited="2020-09-25T20:00:00Z">
+    <!-- a comment inside a removed bookmark -->
+    <info>
+      <?processing instruction?>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="text/plain"/>
+        <bookmark:groups>
+          <bookmark:group><![CDATA[Text & Code]]></bookmark:group>
+        </bookmark:groups>
+      </metadata>
+    </info>
+  </bookmark>
+  <!-- trailing &amp; comment -->
+</xbel>
+<!-- the end -->
+"#;
+        let mut output = Vec::new();
+        read_filter_write(input.as_bytes(), &mut output, &Filter::default()).unwrap();
+        assert_eq!(input, String::from_utf8(output).unwrap());
+
+        let expected = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE xbel PUBLIC "+//IDN python.org//DTD XML Bookmark Exchange Language 1.0//EN//XML" "http://pyxml.sourceforge.net/topics/dtds/xbel-1.0.dtd">
+<?xml-stylesheet type="text/css" href="xbel.css"?>
+<!-- edited by hand -->
+<xbel version="1.0"
+      xmlns:bookmark="http://www.freedesktop.org/standards/desktop-bookmarks"
+      xmlns:mime="http://www.freedesktop.org/standards/shared-mime-info"
+>
+  <title>Recently &amp; Frequently Used &#x1F4DD;</title>
+  <desc><![CDATA[Files <opened> recently]]></desc>
+  <!-- kept -->
+  <bookmark href="file:///home/me/A-File.txt" added="2020-09-24T20:00:00Z" modified="2020-09-25T20:00:00Z" visited="2020-09-25T20:00:00Z">
+    <!-- a comment inside a kept bookmark -->
+    <info>
+      <?processing instruction?>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="text/plain"/>
+        <bookmark:groups>
+          <bookmark:group><![CDATA[Text & Code]]></bookmark:group>
+        </bookmark:groups>
+      </metadata>
+    </info>
+  </bookmark>
+  <!-- removed -->
+  <!-- trailing &amp; comment -->
+</xbel>
+<!-- the end -->
+"#;
+        let mut output = Vec::new();
+        let summary = read_filter_write(
+            input.as_bytes(),
+            &mut output,
+            &Filter::from(vec!["/tmp".parse().unwrap()]),
+        )
+        .unwrap();
+        assert_eq!(expected, String::from_utf8(output).unwrap());
+        assert_eq!(1, summary.kept);
+        assert_eq!(1, summary.removed.len());
+    }
+
+    #[test]
+    fn tolerate_invalid_utf8() {
+        let input = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xbel version="1.0"
+      xmlns:bookmark="http://www.freedesktop.org/standards/desktop-bookmarks"
+      xmlns:mime="http://www.freedesktop.org/standards/shared-mime-info"
+>
+  <bookmark href="file:///opt/A%20Directory/A-File.txt%BC" added="2022-04-08T20:00:00Z" modified="2022-04-08T20:00:00Z" visited="2022-04-08T20:00:00Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="text/plain"/>
+        <bookmark:groups>
+          <bookmark:group>gedit</bookmark:group>
+        </bookmark:groups>
+        <bookmark:applications>
+          <bookmark:application name="gedit" exec="&apos;gedit %u&apos;" modified="2022-04-08T20:00:00Z" count="1234"/>
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>
+  <bookmark href="file:///opt/Another%20Directory/Another-File.txt%BC" added="2022-04-08T20:00:00Z" modified="22022-04-08T20:00:00Z" visited="2022-04-08T20:00:00Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="text/plain"/>
+        <bookmark:groups>
+          <bookmark:group>gedit</bookmark:group>
+        </bookmark:groups>
+        <bookmark:applications>
+          <bookmark:application name="gedit" exec="&apos;gedit %u&apos;" modified="2022-04-08T20:00:00Z" count="1234"/>
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>
+</xbel>
+"#;
+        let mut output = Vec::new();
+        read_filter_write(
+            BufReader::new(input.as_bytes()),
+            &mut output,
+            &Filter::from(vec!["/opt/A Directory".parse().unwrap()]),
+        )
+        .unwrap();
+        let expected = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xbel version="1.0"
+      xmlns:bookmark="http://www.freedesktop.org/standards/desktop-bookmarks"
+      xmlns:mime="http://www.freedesktop.org/standards/shared-mime-info"
+>
+  <bookmark href="file:///opt/Another%20Directory/Another-File.txt%BC" added="2022-04-08T20:00:00Z" modified="22022-04-08T20:00:00Z" visited="2022-04-08T20:00:00Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="text/plain"/>
+        <bookmark:groups>
+          <bookmark:group>gedit</bookmark:group>
+        </bookmark:groups>
+        <bookmark:applications>
+          <bookmark:application name="gedit" exec="&apos;gedit %u&apos;" modified="2022-04-08T20:00:00Z" count="1234"/>
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>
+</xbel>
+"#;
+        assert_eq!(expected, String::from_utf8(output).unwrap());
+    }
+
+    #[test]
+    fn other_protocols() {
+        let input = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xbel version="1.0"
+      xmlns:bookmark="http://www.freedesktop.org/standards/desktop-bookmarks"
+      xmlns:mime="http://www.freedesktop.org/standards/shared-mime-info"
+>
+  <bookmark href="trash:///A-File.txt" added="2020-09-24T20:00:00Z" modified="2020-09-25T20:00:00Z" visited="2020-09-25T20:00:00Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="text/plain"/>
+        <bookmark:groups>
+          <bookmark:group>gedit</bookmark:group>
+        </bookmark:groups>
+        <bookmark:applications>
+          <bookmark:application name="gedit" exec="&apos;gedit %u&apos;" modified="2020-09-25T20:00:00Z" count="1234"/>
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>
+  <bookmark href="mtp://phone_model/Path/To/File.txt" added="2020-09-24T20:00:00Z" modified="2020-09-25T20:00:00Z" visited="2020-09-25T20:00:00Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="text/plain"/>
+        <bookmark:groups>
+          <bookmark:group>gedit</bookmark:group>
+        </bookmark:groups>
+        <bookmark:applications>
+          <bookmark:application name="gedit" exec="&apos;gedit %u&apos;" modified="2020-09-25T20:00:00Z" count="1234"/>
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>
+  <bookmark href="ftp://user@host/Path/To/File" added="2021-09-14T18:00:00Z" modified="2021-09-14T18:00:00Z" visited="2021-09-14T18:00:00Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="application/x-php"/>
+        <bookmark:groups>
+          <bookmark:group>gedit</bookmark:group>
+        </bookmark:groups>
+        <bookmark:applications>
+          <bookmark:application name="gedit" exec="&apos;gedit %u&apos;" modified="2021-09-14T18:00:00Z" count="1234"/>
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>
+  <bookmark href="sftp://user@host/Path/To/File" added="2021-09-14T18:00:00Z" modified="2021-09-14T18:00:00Z" visited="2021-09-14T18:00:00Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="application/x-php"/>
+        <bookmark:groups>
+          <bookmark:group>gedit</bookmark:group>
+        </bookmark:groups>
+        <bookmark:applications>
+          <bookmark:application name="gedit" exec="&apos;gedit %u&apos;" modified="2021-09-14T18:00:00Z" count="1234"/>
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>
+  <bookmark href="cdda://sr0/" added="2025-09-13T11:59:30.774505Z" modified="2025-09-13T11:59:30.774508Z" visited="2025-09-13T11:59:30.774506Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="inode/directory"/>
+        <bookmark:applications>
+          <bookmark:application name="org.gnome.Nautilus" exec="&apos;brasero %U&apos;" modified="2025-09-13T11:59:30.774507Z" count="1"/>
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>
+</xbel>
+"#;
+        let mut output = Vec::new();
+        read_filter_write(
+            BufReader::new(input.as_bytes()),
+            &mut output,
+            &Filter::default(),
+        )
+        .unwrap();
+        assert_eq!(input, String::from_utf8(output).unwrap());
+    }
+
+    #[test]
+    fn unknown_schemes() {
+        let input = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xbel version="1.0">
+  <bookmark href="file:///home/me/A-File.txt" added="2021-09-14T18:00:00Z" modified="2021-09-14T18:00:00Z" visited="2021-09-14T18:00:00Z">
+  </bookmark>
+  <bookmark href="afc://my-iphone/A-File.txt" added="2021-09-14T18:00:00Z" modified="2021-09-14T18:00:00Z" visited="2021-09-14T18:00:00Z">
+  </bookmark>
+  <bookmark href="google-drive://me@gmail.com/A-File.txt" added="2021-09-14T18:00:00Z" modified="2021-09-14T18:00:00Z" visited="2021-09-14T18:00:00Z">
+  </bookmark>
+</xbel>
+"#;
+        let filter_with = |unknown_schemes, schemes: &[&str], rules: &[&str]| {
+            let filter = Filter {
+                unknown_schemes,
+                schemes: schemes.iter().map(|scheme| scheme.to_string()).collect(),
+                ..Filter::from(
+                    rules
+                        .iter()
+                        .map(|rule| rule.parse().unwrap())
+                        .collect::<Vec<_>>(),
+                )
+            };
+            let mut output = Vec::new();
+            read_filter_write(input.as_bytes(), &mut output, &filter)
+                .map(|summary| (summary, String::from_utf8(output).unwrap()))
+        };
+
+        assert!(matches!(
+            filter_with(UnknownSchemes::Fail, &[], &[]),
+            Err(XbelError::HrefNotRecognized { position: 215, .. })
+        ));
+        assert!(matches!(
+            filter_with(UnknownSchemes::Fail, &["afc"], &[]),
+            Err(XbelError::HrefNotRecognized { position: 368, .. })
+        ));
+        let (_, output) = filter_with(UnknownSchemes::Fail, &["afc", "google-drive"], &[]).unwrap();
+        assert_eq!(input, output);
+        let (_, output) = filter_with(UnknownSchemes::Keep, &[], &[]).unwrap();
+        assert_eq!(input, output);
+        let (_, output) = filter_with(UnknownSchemes::Warn, &[], &[]).unwrap();
+        assert_eq!(input, output);
+        let (summary, _) = filter_with(UnknownSchemes::Keep, &[], &["regex:^afc:"]).unwrap();
+        assert_eq!(
+            vec!["afc://my-iphone/A-File.txt"],
+            summary
+                .removed
+                .iter()
+                .map(|removal| removal.href.as_str())
+                .collect::<Vec<_>>()
+        );
+
+        let (summary, output) = filter_with(UnknownSchemes::Remove, &["afc"], &[]).unwrap();
+        let expected = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xbel version="1.0">
+  <bookmark href="file:///home/me/A-File.txt" added="2021-09-14T18:00:00Z" modified="2021-09-14T18:00:00Z" visited="2021-09-14T18:00:00Z">
+  </bookmark>
+  <bookmark href="afc://my-iphone/A-File.txt" added="2021-09-14T18:00:00Z" modified="2021-09-14T18:00:00Z" visited="2021-09-14T18:00:00Z">
+  </bookmark>
+</xbel>
+"#;
+        assert_eq!(expected, output);
+        assert_eq!(2, summary.kept);
+        assert_eq!(
+            vec![Removal {
+                href: String::from("google-drive://me@gmail.com/A-File.txt"),
+                path: None,
+                reason: Reason::UnknownScheme,
+            }],
+            summary.removed
+        );
+        let (summary, output) = filter_with(
+            UnknownSchemes::Remove,
+            &["afc"],
+            &["keep:regex:^google-drive://me@"],
+        )
+        .unwrap();
+        assert_eq!(input, output);
+        assert_eq!(3, summary.kept);
     }
 }
@@ -0,0 +1,231 @@
+use crate::error::XbelError;
+use crate::rules::{Filter, Reason};
+use chrono::{Local, SecondsFormat};
+use clap::ValueEnum;
+use serde::Serialize;
+use std::error::Error;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// A bookmark removed by the filter.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Removal {
+    /// The href as written in the file (percent-encoded).
+    pub href: String,
+    /// The decoded local path, for `file://` hrefs.
+    pub path: Option<String>,
+    #[serde(flatten)]
+    pub reason: Reason,
+}
+
+/// What filtering a document did.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Summary {
+    /// The number of bookmarks kept.
+    pub kept: usize,
+    /// The bookmarks removed, in document order.
+    pub removed: Vec<Removal>,
+}
+
+/// Print what a dry run would have removed.
+pub fn print_dry_run(summary: &Summary, out: &mut impl Write) -> io::Result<()> {
+    for removal in &summary.removed {
+        match &removal.path {
+            Some(path) => writeln!(out, "{} ({path}): {}", removal.href, removal.reason)?,
+            None => writeln!(out, "{}: {}", removal.href, removal.reason)?,
+        }
+    }
+    writeln!(
+        out,
+        "Would remove {} entries and keep {}.",
+        summary.removed.len(),
+        summary.kept
+    )
+}
+
+/// The format of a machine-readable report.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum ReportFormat {
+    /// A pretty-printed JSON object, replacing the report file.
+    #[default]
+    Json,
+    /// The JSON object on a single line, appended to the report file,
+    /// so that the file collects the reports of many runs.
+    JsonLines,
+}
+
+/// A machine-readable report of one run.
+#[derive(Debug, Serialize)]
+pub struct Report<'a> {
+    /// When the run happened.
+    pub time: String,
+    pub input: &'a Path,
+    pub dry_run: bool,
+    pub rules: Vec<String>,
+    pub prune_apps: &'a [String],
+    pub max_entries: Option<usize>,
+    pub kept: usize,
+    pub removed: usize,
+    pub removals: &'a [Removal],
+}
+
+impl<'a> Report<'a> {
+    pub fn new(input: &'a Path, dry_run: bool, filter: &'a Filter, summary: &'a Summary) -> Self {
+        Report {
+            time: Local::now().to_rfc3339_opts(SecondsFormat::Secs, false),
+            input,
+            dry_run,
+            rules: filter.rules.iter().map(|rule| rule.to_string()).collect(),
+            prune_apps: &filter.prune_applications,
+            max_entries: filter.max_entries,
+            kept: summary.kept,
+            removed: summary.removed.len(),
+            removals: &summary.removed,
+        }
+    }
+
+    pub fn write_to(
+        &self,
+        out: &mut impl Write,
+        format: ReportFormat,
+    ) -> Result<(), Box<dyn Error>> {
+        match format {
+            ReportFormat::Json => serde_json::to_writer_pretty(&mut *out, self)?,
+            ReportFormat::JsonLines => serde_json::to_writer(&mut *out, self)?,
+        }
+        writeln!(out)?;
+        out.flush()?;
+        Ok(())
+    }
+}
+
+/// Open the file to write a report to (replacing or appending to it, depending on the format),
+/// or standard output if the file is `-`.
+/// This is done before changing anything, so that an unusable report file leaves everything unchanged.
+pub fn open(file: &Path, format: ReportFormat) -> Result<Box<dyn Write>, XbelError> {
+    if file == Path::new("-") {
+        return Ok(Box::new(io::stdout()));
+    }
+    let opened = match format {
+        ReportFormat::Json => File::create(file),
+        ReportFormat::JsonLines => OpenOptions::new().append(true).create(true).open(file),
+    };
+    Ok(Box::new(
+        opened.map_err(|error| XbelError::file(file, error))?,
+    ))
+}
+
+/// Whether the report goes to standard output (rather than to a file).
+pub fn is_stdout(report: &Option<PathBuf>) -> bool {
+    report.as_deref() == Some(Path::new("-"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn summary() -> Summary {
+        Summary {
+            kept: 3,
+            removed: vec![
+                Removal {
+                    href: String::from("file:///tmp/A%20File.txt"),
+                    path: Some(String::from("/tmp/A File.txt")),
+                    reason: Reason::Rule(String::from("/tmp")),
+                },
+                Removal {
+                    href: String::from("sftp://user@prod-db/Path/To/File"),
+                    path: None,
+                    reason: Reason::MaxEntries,
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn json() {
+        let filter = Filter {
+            prune_applications: vec![String::from("vlc")],
+            max_entries: Some(4),
+            ..Filter::from(vec![
+                "/tmp".parse().unwrap(),
+                "keep:/tmp/keep".parse().unwrap(),
+            ])
+        };
+        let summary = summary();
+        let mut report = Report::new(
+            Path::new("/home/me/.local/share/recently-used.xbel"),
+            true,
+            &filter,
+            &summary,
+        );
+        report.time = String::from("2025-10-15T12:00:00+02:00");
+        let mut output = Vec::new();
+        report.write_to(&mut output, ReportFormat::Json).unwrap();
+        let expected = r#"{
+  "time": "2025-10-15T12:00:00+02:00",
+  "input": "/home/me/.local/share/recently-used.xbel",
+  "dry_run": true,
+  "rules": [
+    "/tmp",
+    "keep:/tmp/keep"
+  ],
+  "prune_apps": [
+    "vlc"
+  ],
+  "max_entries": 4,
+  "kept": 3,
+  "removed": 2,
+  "removals": [
+    {
+      "href": "file:///tmp/A%20File.txt",
+      "path": "/tmp/A File.txt",
+      "reason": "rule",
+      "rule": "/tmp"
+    },
+    {
+      "href": "sftp://user@prod-db/Path/To/File",
+      "path": null,
+      "reason": "max-entries"
+    }
+  ]
+}
+"#;
+        assert_eq!(expected, String::from_utf8(output).unwrap());
+    }
+
+    #[test]
+    fn json_lines() {
+        let filter = Filter::default();
+        let summary = summary();
+        let mut report = Report::new(Path::new("-"), false, &filter, &summary);
+        report.time = String::from("2025-10-15T12:00:00+02:00");
+        let mut output = Vec::new();
+        report
+            .write_to(&mut output, ReportFormat::JsonLines)
+            .unwrap();
+        report
+            .write_to(&mut output, ReportFormat::JsonLines)
+            .unwrap();
+        let output = String::from_utf8(output).unwrap();
+        let lines: Vec<_> = output.lines().collect();
+        assert_eq!(2, lines.len());
+        assert_eq!(lines[0], lines[1]);
+        assert!(lines[0].starts_with(
+            r#"{"time":"2025-10-15T12:00:00+02:00","input":"-","dry_run":false,"rules":[],"#
+        ));
+    }
+
+    #[test]
+    fn open_error() {
+        let file = Path::new("/nonexistent/report.json");
+        for format in [ReportFormat::Json, ReportFormat::JsonLines] {
+            assert!(matches!(
+                open(file, format),
+                Err(XbelError::Io { path: Some(path), .. }) if path == file
+            ));
+        }
+    }
+}
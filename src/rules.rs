@@ -0,0 +1,910 @@
+use crate::bookmark::{Bookmark, Timestamp};
+use crate::remote::RemotePattern;
+use chrono::{DateTime, Local, NaiveDate, SecondsFormat, TimeDelta, Utc};
+use clap::ValueEnum;
+use glob::{MatchOptions, Pattern, PatternError};
+use regex::Regex;
+use serde::de::{self, Deserializer};
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io::ErrorKind;
+use std::os::unix::fs::MetadataExt;
+use std::path::Path;
+use std::str::FromStr;
+
+#[derive(Debug)]
+pub struct RuleSyntaxError {
+    rule: String,
+    message: String,
+}
+impl fmt::Display for RuleSyntaxError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "RuleSyntaxError: {}: {}", self.rule, self.message)
+    }
+}
+impl Error for RuleSyntaxError {}
+
+const GLOB_OPTIONS: MatchOptions = MatchOptions {
+    case_sensitive: true,
+    require_literal_separator: true,
+    require_literal_leading_dot: false,
+};
+
+/// A rule selecting bookmarks to be removed (or kept).
+///
+/// Rules are written as `kind:value` on the command line;
+/// arguments without a recognized kind are path prefixes, which must be absolute.
+/// Any rule can be turned into a keep rule by prefixing it with `keep:`.
+#[derive(Debug, Clone)]
+pub enum Rule {
+    /// Paths equal to or below this directory (or file),
+    /// compared component by component, so `/home/a` does not match `/home/alice`.
+    Prefix(String),
+    /// Paths starting with this string, without regard for component boundaries.
+    /// Written as `raw:/home/a`.
+    RawPrefix(String),
+    /// Paths matching this shell-style glob, written as `glob:/home/*/Downloads/**`.
+    /// `*` and `?` do not match `/`, but `**` matches any number of directories.
+    /// A pattern without any `/`, such as `glob:*.kdbx`, is matched against the file name only.
+    Glob(Pattern),
+    /// Hrefs (of any scheme, after percent-decoding) matching this regular expression,
+    /// written as `regex:^sftp://.*@prod-`.
+    /// The expression is not anchored unless it uses `^` or `$` itself.
+    Regex(Regex),
+    /// Bookmarks whose timestamp is before this point in time, written as
+    /// `visited-before:2025-01-01` (or another timestamp, e.g. `added-before:…`)
+    /// or relative to now as `visited-older-than:30d`.
+    /// Bookmarks with a missing or malformed timestamp never match.
+    Before(Timestamp, DateTime<Utc>),
+    /// Local files that no longer exist, written as `missing`.
+    /// Files on removable media that is not currently mounted are not considered missing,
+    /// unless `unmounted` is set, written as `missing:all`.
+    Missing { unmounted: bool },
+    /// Bookmarks added or visited since this point in time,
+    /// written relative to now as `recent:1h` to forget everything opened in the last hour
+    /// (or as `recent:2025-01-01T10:00:00Z` for a fixed point in time).
+    Recent(DateTime<Utc>),
+    /// Bookmarks with this MIME type, written as `mime:application/pdf`,
+    /// or with any subtype of a type, written as `mime:image/*`.
+    MimeType(String),
+    /// Bookmarks registered by this application, written as `app:org.gnome.Nautilus`.
+    Application(String),
+    /// Bookmarks in this group, written as `group:gedit`.
+    Group(String),
+    /// Remote bookmarks on this host (compared case-insensitively), written as `host:ci-host`.
+    Host(String),
+    /// Remote bookmarks of this user, written as `user:build`
+    /// (or `user:WORKGROUP;build` for smb with a domain).
+    User(String),
+    /// Remote bookmarks matching all the given parts of a location,
+    /// written as `remote:[scheme://][user@]host[:port][/path]`,
+    /// e.g. `remote:sftp://build@ci-host/var/tmp`.
+    /// The path matches paths equal to or below it, like a path prefix.
+    Remote(RemotePattern),
+    /// Bookmarks matching the inner rule are kept, even if an earlier rule matched them,
+    /// written as `keep:/home/me/work/shared`.
+    Keep(Box<Rule>),
+}
+
+/// Directories below which removable media is mounted.
+const REMOVABLE_MEDIA_ROOTS: [&str; 3] = ["/run/media", "/media", "/mnt"];
+
+/// Whether the file at `path` does not exist.
+/// Errors other than the file not being found (e.g. permission denied) do not count as missing.
+fn file_missing(path: &Path, unmounted: bool, removable_media_roots: &[&str]) -> bool {
+    match fs::symlink_metadata(path) {
+        Err(e) if e.kind() == ErrorKind::NotFound => {
+            unmounted || !on_unmounted_media(path, removable_media_roots)
+        }
+        _ => false,
+    }
+}
+
+/// Whether `path` (which does not exist) belongs to removable media that is not mounted,
+/// i.e. its closest existing ancestor is still on the same file system as the removable media root.
+fn on_unmounted_media(path: &Path, removable_media_roots: &[&str]) -> bool {
+    let Some(root) = removable_media_roots
+        .iter()
+        .find(|root| path.starts_with(root))
+    else {
+        return false;
+    };
+    let Ok(root_metadata) = fs::metadata(root) else {
+        return true;
+    };
+    path.ancestors()
+        .find_map(|ancestor| fs::metadata(ancestor).ok())
+        .is_none_or(|metadata| metadata.dev() == root_metadata.dev())
+}
+
+/// Whether `mime_type` matches `pattern`, which may end in `/*` to match all subtypes.
+/// MIME types are compared case-insensitively.
+fn mime_type_matches(pattern: &str, mime_type: &str) -> bool {
+    match pattern.strip_suffix("/*") {
+        Some(pattern) => mime_type
+            .split_once('/')
+            .is_some_and(|(media_type, _)| media_type.eq_ignore_ascii_case(pattern)),
+        None => mime_type.eq_ignore_ascii_case(pattern),
+    }
+}
+
+const TIMESTAMPS: [(&str, Timestamp); 3] = [
+    ("added", Timestamp::Added),
+    ("modified", Timestamp::Modified),
+    ("visited", Timestamp::Visited),
+];
+
+/// Parse a duration like `30d`, with one of the units
+/// `s` (seconds), `m` (minutes), `h` (hours), `d` (days) or `w` (weeks).
+pub fn parse_duration(s: &str) -> Result<TimeDelta, String> {
+    let unit_start = s
+        .find(|c: char| !c.is_ascii_digit())
+        .ok_or_else(|| format!("duration {s:?} has no unit"))?;
+    let (number, unit) = s.split_at(unit_start);
+    let number: i64 = number
+        .parse()
+        .map_err(|_| format!("duration {s:?} does not start with a number"))?;
+    let duration = match unit {
+        "s" => TimeDelta::try_seconds(number),
+        "m" => TimeDelta::try_minutes(number),
+        "h" => TimeDelta::try_hours(number),
+        "d" => TimeDelta::try_days(number),
+        "w" => TimeDelta::try_weeks(number),
+        _ => return Err(format!("unknown unit {unit:?} in duration {s:?}")),
+    };
+    duration.ok_or_else(|| format!("duration {s:?} is too long"))
+}
+
+/// Parse a point in time, either as an RFC 3339 timestamp
+/// or as a date (meaning midnight local time at the start of that day).
+fn parse_time(s: &str) -> Result<DateTime<Utc>, String> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(s) {
+        return Ok(time.with_timezone(&Utc));
+    }
+    NaiveDate::parse_from_str(s, "%Y-%m-%d")
+        .ok()
+        .and_then(|date| {
+            date.and_time(Default::default())
+                .and_local_timezone(Local)
+                .earliest()
+        })
+        .map(|time| time.with_timezone(&Utc))
+        .ok_or_else(|| format!("{s:?} is neither a date (YYYY-MM-DD) nor an RFC 3339 timestamp"))
+}
+
+impl FromStr for Rule {
+    type Err = RuleSyntaxError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let syntax_error = |message: String| RuleSyntaxError {
+            rule: s.to_string(),
+            message,
+        };
+        let unknown_kind = || {
+            syntax_error(String::from(
+                "neither an absolute path nor a known kind of rule",
+            ))
+        };
+        if let Some(rule) = s.strip_prefix("keep:") {
+            match rule.parse()? {
+                Rule::Keep(_) => Err(syntax_error(String::from("keep rules cannot be nested"))),
+                rule => Ok(Rule::Keep(Box::new(rule))),
+            }
+        } else if let Some(prefix) = s.strip_prefix("raw:") {
+            Ok(Rule::RawPrefix(prefix.to_string()))
+        } else if let Some(pattern) = s.strip_prefix("glob:") {
+            Pattern::new(pattern)
+                .map(Rule::Glob)
+                .map_err(|e: PatternError| syntax_error(e.to_string()))
+        } else if s == "missing" {
+            Ok(Rule::Missing { unmounted: false })
+        } else if s == "missing:all" {
+            Ok(Rule::Missing { unmounted: true })
+        } else if let Some(window) = s.strip_prefix("recent:") {
+            parse_duration(window)
+                .map(|window| (Local::now() - window).to_utc())
+                .or_else(|_| parse_time(window))
+                .map(Rule::Recent)
+                .map_err(|_| {
+                    syntax_error(format!(
+                        "{window:?} is neither a duration nor a point in time"
+                    ))
+                })
+        } else if let Some(mime_type) = s.strip_prefix("mime:") {
+            Ok(Rule::MimeType(mime_type.to_string()))
+        } else if let Some(application) = s.strip_prefix("app:") {
+            Ok(Rule::Application(application.to_string()))
+        } else if let Some(group) = s.strip_prefix("group:") {
+            Ok(Rule::Group(group.to_string()))
+        } else if let Some(host) = s.strip_prefix("host:") {
+            Ok(Rule::Host(host.to_string()))
+        } else if let Some(user) = s.strip_prefix("user:") {
+            Ok(Rule::User(user.to_string()))
+        } else if let Some(pattern) = s.strip_prefix("remote:") {
+            pattern.parse().map(Rule::Remote).map_err(syntax_error)
+        } else if let Some(regex) = s.strip_prefix("regex:") {
+            Regex::new(regex)
+                .map(Rule::Regex)
+                .map_err(|e| syntax_error(e.to_string()))
+        } else if let Some((kind, value)) = s.split_once(':')
+            && let Some((timestamp, rule)) = TIMESTAMPS
+                .iter()
+                .find_map(|(name, timestamp)| Some((timestamp, kind.strip_prefix(name)?)))
+        {
+            let cutoff = match rule {
+                "-before" => parse_time(value),
+                "-older-than" => parse_duration(value).map(|age| (Local::now() - age).to_utc()),
+                _ => return Err(unknown_kind()),
+            };
+            cutoff
+                .map(|cutoff| Rule::Before(*timestamp, cutoff))
+                .map_err(syntax_error)
+        } else if Path::new(s).is_absolute() {
+            Ok(Rule::Prefix(s.to_string()))
+        } else {
+            Err(unknown_kind())
+        }
+    }
+}
+
+/// Formats the rule in the same syntax it is parsed from.
+/// Relative times (`-older-than`, `recent:`) are written as the absolute time they resolved to.
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let time = |time: &DateTime<Utc>| time.to_rfc3339_opts(SecondsFormat::Secs, true);
+        match self {
+            Rule::Prefix(prefix) => write!(f, "{prefix}"),
+            Rule::RawPrefix(prefix) => write!(f, "raw:{prefix}"),
+            Rule::Glob(pattern) => write!(f, "glob:{}", pattern.as_str()),
+            Rule::Regex(regex) => write!(f, "regex:{}", regex.as_str()),
+            Rule::Before(timestamp, cutoff) => write!(f, "{timestamp}-before:{}", time(cutoff)),
+            Rule::Missing { unmounted: false } => write!(f, "missing"),
+            Rule::Missing { unmounted: true } => write!(f, "missing:all"),
+            Rule::Recent(cutoff) => write!(f, "recent:{}", time(cutoff)),
+            Rule::MimeType(mime_type) => write!(f, "mime:{mime_type}"),
+            Rule::Application(name) => write!(f, "app:{name}"),
+            Rule::Group(name) => write!(f, "group:{name}"),
+            Rule::Host(host) => write!(f, "host:{host}"),
+            Rule::User(user) => write!(f, "user:{user}"),
+            Rule::Remote(pattern) => write!(f, "remote:{pattern}"),
+            Rule::Keep(rule) => write!(f, "keep:{rule}"),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Rule {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(de::Error::custom)
+    }
+}
+
+impl Rule {
+    /// Whether this rule matches the bookmark.
+    /// Rules based on paths only ever match `file://` bookmarks,
+    /// and rules based on hosts and users only ever match remote ones.
+    pub fn matches(&self, bookmark: &Bookmark) -> bool {
+        match self {
+            Rule::Regex(regex) => regex.is_match(&bookmark.href),
+            Rule::Before(timestamp, cutoff) => bookmark
+                .timestamp(*timestamp)
+                .is_some_and(|time| time < *cutoff),
+            Rule::Recent(cutoff) => [bookmark.added, bookmark.visited]
+                .iter()
+                .flatten()
+                .any(|time| time >= cutoff),
+            Rule::MimeType(pattern) => bookmark
+                .mime_type
+                .as_deref()
+                .is_some_and(|mime_type| mime_type_matches(pattern, mime_type)),
+            Rule::Application(name) => bookmark
+                .applications
+                .iter()
+                .any(|application| application.name == *name),
+            Rule::Group(name) => bookmark.groups.contains(name),
+            Rule::Missing { unmounted } => bookmark
+                .local_path()
+                .is_some_and(|path| file_missing(&path, *unmounted, &REMOVABLE_MEDIA_ROOTS)),
+            Rule::Host(host) => bookmark
+                .remote()
+                .is_some_and(|remote| remote.host.eq_ignore_ascii_case(host)),
+            Rule::User(user) => bookmark
+                .remote()
+                .is_some_and(|remote| remote.user.as_ref() == Some(user)),
+            Rule::Remote(pattern) => bookmark
+                .remote()
+                .is_some_and(|remote| pattern.matches(&remote)),
+            Rule::Keep(rule) => rule.matches(bookmark),
+            _ => bookmark.path().is_some_and(|path| self.matches_path(path)),
+        }
+    }
+
+    fn matches_path(&self, path: &str) -> bool {
+        match self {
+            Rule::Prefix(prefix) => Path::new(path).starts_with(prefix),
+            Rule::RawPrefix(prefix) => path.starts_with(prefix),
+            Rule::Glob(pattern) => {
+                if pattern.as_str().contains('/') {
+                    pattern.matches_with(path, GLOB_OPTIONS)
+                } else {
+                    Path::new(path)
+                        .file_name()
+                        .and_then(|name| name.to_str())
+                        .is_some_and(|name| pattern.matches_with(name, GLOB_OPTIONS))
+                }
+            }
+            // checked on the exact path in `matches`, since the decoded href is lossy
+            Rule::Missing { .. }
+            | Rule::Regex(_)
+            | Rule::Before(..)
+            | Rule::Recent(_)
+            | Rule::MimeType(_)
+            | Rule::Application(_)
+            | Rule::Group(_)
+            | Rule::Host(_)
+            | Rule::User(_)
+            | Rule::Remote(_)
+            | Rule::Keep(_) => false,
+        }
+    }
+}
+
+/// Why a bookmark was removed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "reason", content = "rule", rename_all = "kebab-case")]
+pub enum Reason {
+    /// This rule (the last one matching the bookmark) removed it.
+    Rule(String),
+    /// No applications were left after pruning them.
+    NoApplications,
+    /// It was not among the newest bookmarks kept by `max_entries`.
+    MaxEntries,
+    /// Its scheme is not known, and unknown schemes are removed.
+    UnknownScheme,
+}
+
+impl fmt::Display for Reason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Reason::Rule(rule) => write!(f, "rule {rule}"),
+            Reason::NoApplications => write!(f, "no applications left after pruning"),
+            Reason::MaxEntries => write!(f, "not among the newest entries"),
+            Reason::UnknownScheme => write!(f, "unknown scheme"),
+        }
+    }
+}
+
+/// Schemes other than `file` whose bookmarks are understood (and kept unless a rule matches them):
+/// local ones, followed by the network ones GVfs produces, which `host:`, `user:` and `remote:` rules can match.
+const KNOWN_SCHEMES: [&str; 16] = [
+    "trash", "mtp", "cdda", "sftp", "ssh", "ftp", "ftps", "smb", "dav", "davs", "dav+sd",
+    "davs+sd", "afp", "nfs", "http", "https",
+];
+
+/// What happens to bookmarks whose scheme is not known.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum UnknownSchemes {
+    /// Fail without changing anything.
+    #[default]
+    Fail,
+    /// Treat them like any other entry, i.e. keep them unless a rule matches them.
+    Keep,
+    /// Like keep, but print a warning for each of them.
+    Warn,
+    /// Remove them, unless a keep rule matches them.
+    Remove,
+}
+
+/// Everything that determines which bookmarks are removed or edited.
+#[derive(Debug, Clone, Default)]
+pub struct Filter {
+    /// The rules selecting which bookmarks are removed.
+    /// The last rule that matches a bookmark decides whether it is removed or kept,
+    /// so later rules can make exceptions to earlier ones;
+    /// bookmarks that no rule matches are kept.
+    pub rules: Vec<Rule>,
+    /// Applications whose registration is removed from every bookmark.
+    /// Bookmarks left without any application are removed entirely.
+    pub prune_applications: Vec<String>,
+    /// If set, only this many of the bookmarks that are not otherwise removed are kept,
+    /// the newest according to `max_entries_by`.
+    pub max_entries: Option<usize>,
+    pub max_entries_by: Timestamp,
+    /// Schemes that are known in addition to `file` and `KNOWN_SCHEMES`.
+    pub schemes: Vec<String>,
+    pub unknown_schemes: UnknownSchemes,
+}
+
+impl From<Vec<Rule>> for Filter {
+    fn from(rules: Vec<Rule>) -> Self {
+        Filter {
+            rules,
+            ..Default::default()
+        }
+    }
+}
+
+impl Filter {
+    /// Whether the scheme of the href is known:
+    /// `file`, one of the `KNOWN_SCHEMES`, or one of the additional `schemes`.
+    pub fn knows_scheme(&self, href: &str) -> bool {
+        let Some((scheme, _)) = href.split_once("://") else {
+            return false;
+        };
+        ["file"]
+            .iter()
+            .chain(&KNOWN_SCHEMES)
+            .copied()
+            .chain(self.schemes.iter().map(String::as_str))
+            .any(|known| known.eq_ignore_ascii_case(scheme))
+    }
+
+    /// The rule that decides about the bookmark, i.e. the last one matching it.
+    fn deciding_rule(&self, bookmark: &Bookmark) -> Option<&Rule> {
+        self.rules.iter().rev().find(|rule| rule.matches(bookmark))
+    }
+
+    /// Whether the rules select the bookmark for removal
+    /// (disregarding the applications to prune and the `max_entries` limit).
+    pub fn selects(&self, bookmark: &Bookmark) -> bool {
+        matches!(self.deciding_rule(bookmark), Some(rule) if !matches!(rule, Rule::Keep(_)))
+    }
+
+    /// Whether a keep rule decides about the bookmark, so that it is kept no matter what.
+    pub fn keeps(&self, bookmark: &Bookmark) -> bool {
+        matches!(self.deciding_rule(bookmark), Some(Rule::Keep(_)))
+    }
+
+    /// Apply the filter to the bookmark, returning why it should be removed, or `None` to keep it.
+    /// Kept bookmarks may have been edited, unless a keep rule matched them.
+    /// (The `max_entries` limit is not applied here, since it depends on the other bookmarks.)
+    pub fn apply(&self, bookmark: &mut Bookmark) -> Result<Option<Reason>, quick_xml::Error> {
+        match self.deciding_rule(bookmark) {
+            Some(Rule::Keep(_)) => return Ok(None),
+            Some(rule) => return Ok(Some(Reason::Rule(rule.to_string()))),
+            None => (),
+        }
+        if bookmark.applications.is_empty() {
+            return Ok(None);
+        }
+        for name in &self.prune_applications {
+            bookmark.remove_application(name)?;
+        }
+        Ok(bookmark
+            .applications
+            .is_empty()
+            .then_some(Reason::NoApplications))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bookmark::Application;
+    use std::env;
+    use std::ffi::OsStr;
+    use std::os::unix::ffi::OsStrExt;
+    use std::process;
+
+    fn rule(s: &str) -> Rule {
+        s.parse().unwrap()
+    }
+
+    fn bookmark(href: &str) -> Bookmark {
+        Bookmark {
+            href: href.to_string(),
+            encoded_href: href.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn glob_matches_path() {
+        let rule = rule("glob:/home/*/Downloads/**");
+        assert!(rule.matches_path("/home/me/Downloads/A-File.txt"));
+        assert!(rule.matches_path("/home/me/Downloads/A Directory/A-File.txt"));
+        assert!(!rule.matches_path("/home/me/Documents/Downloads/A-File.txt"));
+        assert!(!rule.matches_path("/opt/Downloads/A-File.txt"));
+    }
+
+    #[test]
+    fn glob_matches_anywhere() {
+        let rule = rule("glob:**/*.pdf");
+        assert!(rule.matches_path("/A-File.pdf"));
+        assert!(rule.matches_path("/home/me/A Directory/A-File.pdf"));
+        assert!(!rule.matches_path("/home/me/A-File.pdf.txt"));
+    }
+
+    #[test]
+    fn glob_matches_file_name() {
+        let rule = rule("glob:*.kdbx");
+        assert!(rule.matches_path("/home/me/Passwords.kdbx"));
+        assert!(rule.matches_path("/Passwords.kdbx"));
+        assert!(!rule.matches_path("/home/me/Passwords.kdbx/A-File.txt"));
+    }
+
+    #[test]
+    fn regex_matches_href() {
+        let rule = rule("regex:^sftp://.*@prod-");
+        assert!(rule.matches(&bookmark("sftp://user@prod-db/Path/To/File")));
+        assert!(!rule.matches(&bookmark("sftp://user@staging-db/prod-File")));
+        assert!(!rule.matches(&bookmark("ftp://user@prod-db/Path/To/File")));
+    }
+
+    #[test]
+    fn regex_matches_file_href() {
+        let rule = rule("regex:A Directory/.*\\.txt$");
+        assert!(rule.matches(&bookmark("file:///opt/A Directory/A-File.txt")));
+        assert!(!rule.matches(&bookmark("file:///opt/A Directory/A-File.txt.gz")));
+    }
+
+    #[test]
+    fn before_matches_timestamp() {
+        let visited_rule = rule("visited-before:2025-01-01T00:00:00Z");
+        let added_rule = rule("added-before:2025-01-01");
+        let mut bookmark = bookmark("file:///home/me/A-File.txt");
+        assert!(!visited_rule.matches(&bookmark));
+        bookmark.visited = Some("2024-12-31T23:59:59.999Z".parse().unwrap());
+        assert!(visited_rule.matches(&bookmark));
+        bookmark.visited = Some("2025-01-01T00:00:00Z".parse().unwrap());
+        assert!(!visited_rule.matches(&bookmark));
+        bookmark.added = Some("2020-09-24T20:00:00Z".parse().unwrap());
+        assert!(!visited_rule.matches(&bookmark));
+        assert!(added_rule.matches(&bookmark));
+    }
+
+    #[test]
+    fn older_than() {
+        let Rule::Before(Timestamp::Modified, cutoff) = rule("modified-older-than:30d") else {
+            panic!("not a modified-before rule");
+        };
+        let age = Utc::now() - cutoff;
+        assert!(age >= TimeDelta::days(30) && age < TimeDelta::days(31));
+    }
+
+    #[test]
+    fn recent() {
+        let rule = rule("recent:1h");
+        let mut bookmark = bookmark("sftp://user@host/Path/To/File");
+        assert!(!rule.matches(&bookmark));
+        bookmark.added = Some(Utc::now() - TimeDelta::days(1));
+        bookmark.modified = Some(Utc::now());
+        bookmark.visited = Some(Utc::now() - TimeDelta::minutes(61));
+        assert!(!rule.matches(&bookmark));
+        bookmark.visited = Some(Utc::now() - TimeDelta::minutes(59));
+        assert!(rule.matches(&bookmark));
+        bookmark.added = Some(Utc::now());
+        bookmark.visited = None;
+        assert!(rule.matches(&bookmark));
+    }
+
+    #[test]
+    fn mime_types() {
+        assert!(mime_type_matches("application/pdf", "application/pdf"));
+        assert!(mime_type_matches("image/*", "image/png"));
+        assert!(mime_type_matches("Image/*", "image/svg+xml"));
+        assert!(!mime_type_matches("image/*", "application/pdf"));
+        assert!(!mime_type_matches("image/*", "image"));
+        assert!(!mime_type_matches("application/pdf", "application/pdfx"));
+        let rule = rule("mime:image/*");
+        let mut bookmark = bookmark("file:///home/me/A-File.png");
+        assert!(!rule.matches(&bookmark));
+        bookmark.mime_type = Some(String::from("image/png"));
+        assert!(rule.matches(&bookmark));
+    }
+
+    #[test]
+    fn applications() {
+        let rule = rule("app:vlc");
+        let mut bookmark = bookmark("file:///home/me/A-File.mp4");
+        assert!(!rule.matches(&bookmark));
+        bookmark.applications.push(Application {
+            name: String::from("org.gnome.Nautilus"),
+            count: 1,
+        });
+        assert!(!rule.matches(&bookmark));
+        bookmark.applications.push(Application {
+            name: String::from("vlc"),
+            count: 2,
+        });
+        assert!(rule.matches(&bookmark));
+    }
+
+    #[test]
+    fn remote_rules() {
+        let build = bookmark("sftp://build@CI-Host/var/tmp/A-File.txt");
+        let smb = bookmark("smb://WORKGROUP;build@fileserver/share/A-File.txt");
+        let local = bookmark("file:///var/tmp/A-File.txt");
+        let host = rule("host:ci-host");
+        assert!(host.matches(&build));
+        assert!(!host.matches(&smb));
+        assert!(!host.matches(&local));
+        let user = rule("user:build");
+        assert!(user.matches(&build));
+        assert!(!user.matches(&smb));
+        assert!(rule("user:WORKGROUP;build").matches(&smb));
+        let remote = rule("remote:sftp://build@ci-host/var/tmp");
+        assert!(remote.matches(&build));
+        assert!(!remote.matches(&local));
+        assert!(!rule("remote:ci-host/var/tmp/B-File.txt").matches(&build));
+        assert!(rule("remote:smb://fileserver/share").matches(&smb));
+        assert!(!rule("remote:ftp://fileserver/share").matches(&smb));
+    }
+
+    #[test]
+    fn invalid_remote() {
+        assert!("remote:".parse::<Rule>().is_err());
+        assert!("remote:sftp://ci-host:ssh/var/tmp".parse::<Rule>().is_err());
+    }
+
+    #[test]
+    fn durations() {
+        assert_eq!(Ok(TimeDelta::seconds(90)), parse_duration("90s"));
+        assert_eq!(Ok(TimeDelta::minutes(15)), parse_duration("15m"));
+        assert_eq!(Ok(TimeDelta::hours(1)), parse_duration("1h"));
+        assert_eq!(Ok(TimeDelta::weeks(2)), parse_duration("2w"));
+        assert!(parse_duration("30").is_err());
+        assert!(parse_duration("d").is_err());
+        assert!(parse_duration("30 days").is_err());
+    }
+
+    #[test]
+    fn missing_files() {
+        let dir = env::temp_dir().join(format!("clean-recently-used-test-{}", process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let existing = dir.join("A-File.txt");
+        fs::write(&existing, "").unwrap();
+        let missing = dir.join("Another-File.txt");
+        assert!(!file_missing(&existing, false, &[]));
+        assert!(file_missing(&missing, false, &[]));
+        let rule = rule("missing");
+        assert!(!rule.matches(&bookmark(&format!("file://{}", existing.display()))));
+        assert!(rule.matches(&bookmark(&format!("file://{}", missing.display()))));
+        assert!(!rule.matches(&bookmark("trash:///A-File.txt")));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn missing_files_with_invalid_utf8() {
+        let dir = env::temp_dir().join(format!(
+            "clean-recently-used-test-invalid-utf8-{}",
+            process::id()
+        ));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join(OsStr::from_bytes(b"Caf\xE9.txt")), "").unwrap();
+        let existing = Bookmark {
+            href: format!("file://{}/Caf\u{FFFD}.txt", dir.display()),
+            encoded_href: format!("file://{}/Caf%E9.txt", dir.display()),
+            ..Default::default()
+        };
+        let missing = Bookmark {
+            href: format!("file://{}/Th\u{FFFD}.txt", dir.display()),
+            encoded_href: format!("file://{}/Th%E9.txt", dir.display()),
+            ..Default::default()
+        };
+        let rule = rule("missing");
+        assert!(!rule.matches(&existing));
+        assert!(rule.matches(&missing));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn missing_files_on_unmounted_media() {
+        let media =
+            env::temp_dir().join(format!("clean-recently-used-test-media-{}", process::id()));
+        fs::create_dir_all(&media).unwrap();
+        let media_root = media.to_str().unwrap();
+        let file = media.join("A-Volume/A-File.txt");
+        assert!(!file_missing(&file, false, &[media_root]));
+        assert!(file_missing(&file, true, &[media_root]));
+        fs::remove_dir_all(&media).unwrap();
+        assert!(!file_missing(&file, false, &[media_root]));
+    }
+
+    fn filter(rules: &[&str]) -> Filter {
+        Filter::from(rules.iter().map(|s| rule(s)).collect::<Vec<_>>())
+    }
+
+    fn kept(filter: &Filter, path: &str) -> bool {
+        filter
+            .apply(&mut bookmark(&format!("file://{path}")))
+            .unwrap()
+            .is_none()
+    }
+
+    #[test]
+    fn keep_overrides_earlier_rules() {
+        let filter = filter(&["/home/me/work", "keep:/home/me/work/shared"]);
+        assert!(!kept(&filter, "/home/me/work/A-File.txt"));
+        assert!(kept(&filter, "/home/me/work/shared/A-File.txt"));
+        assert!(kept(&filter, "/home/me/A-File.txt"));
+    }
+
+    #[test]
+    fn later_rules_override_keep() {
+        let filter = filter(&["keep:/home/me/work/shared", "/home/me/work"]);
+        assert!(!kept(&filter, "/home/me/work/A-File.txt"));
+        assert!(!kept(&filter, "/home/me/work/shared/A-File.txt"));
+    }
+
+    #[test]
+    fn nested_keep_and_remove() {
+        let filter = filter(&[
+            "/home/me/work",
+            "keep:/home/me/work/shared",
+            "/home/me/work/shared/tmp",
+            "keep:glob:/home/me/work/shared/tmp/*.keep",
+            "glob:*.kdbx",
+        ]);
+        assert!(!kept(&filter, "/home/me/work/A-File.txt"));
+        assert!(kept(&filter, "/home/me/work/shared/A-File.txt"));
+        assert!(!kept(&filter, "/home/me/work/shared/tmp/A-File.txt"));
+        assert!(kept(&filter, "/home/me/work/shared/tmp/A-File.keep"));
+        assert!(!kept(&filter, "/home/me/work/shared/Passwords.kdbx"));
+        assert!(!kept(&filter, "/home/me/Passwords.kdbx"));
+        assert!(kept(&filter, "/home/me/A-File.txt"));
+    }
+
+    #[test]
+    fn keep_prevents_pruning() {
+        let filter = Filter {
+            prune_applications: vec![String::from("vlc")],
+            ..filter(&["keep:/home/me/Videos"])
+        };
+        let mut bookmark = bookmark("file:///home/me/Videos/A-File.mp4");
+        bookmark.applications.push(Application {
+            name: String::from("vlc"),
+            count: 1,
+        });
+        assert_eq!(None, filter.apply(&mut bookmark).unwrap());
+        assert_eq!(1, bookmark.applications.len());
+    }
+
+    #[test]
+    fn invalid_keep() {
+        assert!("keep:keep:/home/me".parse::<Rule>().is_err());
+        assert!("keep:glob:/home/[".parse::<Rule>().is_err());
+    }
+
+    #[test]
+    fn display() {
+        for s in [
+            "/home/me",
+            "raw:/home/a",
+            "glob:**/*.pdf",
+            "regex:^sftp://.*@prod-",
+            "visited-before:2025-01-01T00:00:00Z",
+            "missing",
+            "missing:all",
+            "recent:2025-10-15T10:00:00Z",
+            "mime:image/*",
+            "app:vlc",
+            "group:gedit",
+            "host:ci-host",
+            "user:WORKGROUP;build",
+            "remote:sftp://build@ci-host:22/var/tmp",
+            "keep:glob:*.txt",
+        ] {
+            assert_eq!(s, rule(s).to_string());
+        }
+        let Rule::Before(_, cutoff) = rule("added-older-than:1h") else {
+            panic!("not a before rule");
+        };
+        assert_eq!(
+            format!(
+                "added-before:{}",
+                cutoff.to_rfc3339_opts(SecondsFormat::Secs, true)
+            ),
+            rule("added-older-than:1h").to_string()
+        );
+    }
+
+    #[test]
+    fn reasons() {
+        let filter = Filter {
+            prune_applications: vec![String::from("vlc")],
+            ..filter(&["/tmp", "glob:*.kdbx"])
+        };
+        let mut passwords = bookmark("file:///tmp/Passwords.kdbx");
+        assert_eq!(
+            Some(Reason::Rule(String::from("glob:*.kdbx"))),
+            filter.apply(&mut passwords).unwrap()
+        );
+        let mut video = bookmark("file:///home/me/A-File.mp4");
+        video.applications.push(Application {
+            name: String::from("vlc"),
+            count: 1,
+        });
+        assert_eq!(
+            Some(Reason::NoApplications),
+            filter.apply(&mut video).unwrap()
+        );
+        assert_eq!(
+            None,
+            filter
+                .apply(&mut bookmark("file:///home/me/A-File.txt"))
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn selects() {
+        let filter = Filter {
+            prune_applications: vec![String::from("vlc")],
+            ..filter(&["/tmp", "keep:glob:*.kdbx"])
+        };
+        assert!(filter.selects(&bookmark("file:///tmp/A-File.txt")));
+        assert!(!filter.selects(&bookmark("file:///tmp/Passwords.kdbx")));
+        let mut video = bookmark("file:///home/me/A-File.mp4");
+        video.applications.push(Application {
+            name: String::from("vlc"),
+            count: 1,
+        });
+        assert!(!filter.selects(&video));
+        assert!(filter.keeps(&bookmark("file:///tmp/Passwords.kdbx")));
+        assert!(!filter.keeps(&bookmark("file:///tmp/A-File.txt")));
+        assert!(!filter.keeps(&video));
+    }
+
+    #[test]
+    fn known_schemes() {
+        let filter = Filter {
+            schemes: vec![String::from("afc"), String::from("google-drive")],
+            ..Default::default()
+        };
+        assert!(filter.knows_scheme("file:///home/me/A-File.txt"));
+        assert!(filter.knows_scheme("trash:///A-File.txt"));
+        assert!(filter.knows_scheme("SFTP://me@example.com/A-File.txt"));
+        assert!(filter.knows_scheme("smb://server/share/A-File.txt"));
+        assert!(filter.knows_scheme("afc://my-iphone/A-File.txt"));
+        assert!(filter.knows_scheme("google-drive://me@gmail.com/A-File.txt"));
+        assert!(!filter.knows_scheme("gphoto2://camera/A-File.jpg"));
+        assert!(!filter.knows_scheme("mailto:me@example.com"));
+        assert!(!Filter::default().knows_scheme("afc://my-iphone/A-File.txt"));
+        for href in [
+            "smb://server/share/A-File.txt",
+            "davs://example.com/A-File.txt",
+            "dav+sd://example._webdav._tcp.local/A-File.txt",
+            "afp://mac.local/Volume/A-File.txt",
+            "nfs://nas/export/A-File.txt",
+        ] {
+            assert!(Filter::default().knows_scheme(href), "{href}");
+        }
+    }
+
+    #[test]
+    fn invalid_glob() {
+        assert!("glob:/home/[".parse::<Rule>().is_err());
+    }
+
+    #[test]
+    fn unknown_kinds() {
+        for s in [
+            "mine:image/*",
+            "grp:gedit",
+            "missing:al",
+            "vistied-before:2025-01-01",
+            "visited-after:2025-01-01",
+            "home/me",
+            "",
+        ] {
+            assert!(s.parse::<Rule>().is_err(), "{s}");
+        }
+        assert!(matches!(rule("/home/me:work"), Rule::Prefix(_)));
+    }
+
+    #[test]
+    fn invalid_regex() {
+        assert!("regex:^(sftp".parse::<Rule>().is_err());
+    }
+
+    #[test]
+    fn invalid_time() {
+        assert!("visited-before:yesterday".parse::<Rule>().is_err());
+        assert!("added-older-than:a month".parse::<Rule>().is_err());
+    }
+}
@@ -0,0 +1,273 @@
+use crate::bookmark::{Bookmark, Timestamp};
+use chrono::{DateTime, TimeDelta, Utc};
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+
+/// The age buckets of the age distribution, with their upper bounds.
+const AGES: [(&str, Option<TimeDelta>); 5] = [
+    ("within the last day", Some(TimeDelta::days(1))),
+    ("within the last week", Some(TimeDelta::weeks(1))),
+    ("within the last 30 days", Some(TimeDelta::days(30))),
+    ("within the last year", Some(TimeDelta::days(365))),
+    ("longer ago", None),
+];
+
+/// Statistics about a list of bookmarks.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Stats {
+    pub entries: usize,
+    /// For each application, how often it opened files (the sum of the counts) and how many entries it registered.
+    pub applications: BTreeMap<String, (u64, usize)>,
+    /// The number of entries per scheme (e.g. `file`).
+    pub schemes: BTreeMap<String, usize>,
+    /// The number of local files per top-level directory (e.g. `/home`).
+    pub directories: BTreeMap<String, usize>,
+    /// The number of entries per MIME type (`None` for entries without one).
+    pub mime_types: BTreeMap<Option<String>, usize>,
+    /// The number of entries per bucket of `AGES`, by when they were last visited,
+    /// followed by the number of entries without a visited timestamp.
+    pub ages: [usize; AGES.len() + 1],
+}
+
+fn scheme(href: &str) -> &str {
+    match href.split_once(':') {
+        Some((scheme, _)) => scheme,
+        None => href,
+    }
+}
+
+fn top_level_directory(path: &str) -> String {
+    let path = path.trim_start_matches('/');
+    format!("/{}", path.split('/').next().unwrap_or_default())
+}
+
+impl Stats {
+    pub fn new(bookmarks: &[Bookmark], now: DateTime<Utc>) -> Self {
+        let mut stats = Stats {
+            entries: bookmarks.len(),
+            ..Default::default()
+        };
+        for bookmark in bookmarks {
+            for application in &bookmark.applications {
+                let (count, entries) = stats
+                    .applications
+                    .entry(application.name.clone())
+                    .or_default();
+                *count += application.count;
+                *entries += 1;
+            }
+            *stats
+                .schemes
+                .entry(scheme(&bookmark.href).to_string())
+                .or_default() += 1;
+            if let Some(path) = bookmark.path() {
+                *stats
+                    .directories
+                    .entry(top_level_directory(path))
+                    .or_default() += 1;
+            }
+            *stats
+                .mime_types
+                .entry(bookmark.mime_type.clone())
+                .or_default() += 1;
+            let bucket = match bookmark.timestamp(Timestamp::Visited) {
+                Some(visited) => AGES
+                    .iter()
+                    .position(|(_, bound)| bound.is_none_or(|bound| now - visited < bound))
+                    .unwrap_or(AGES.len() - 1),
+                None => AGES.len(),
+            };
+            stats.ages[bucket] += 1;
+        }
+        stats
+    }
+
+    /// Print the statistics in a human-readable form.
+    pub fn write(&self, out: &mut impl Write) -> io::Result<()> {
+        writeln!(out, "{} entries", self.entries)?;
+
+        writeln!(out, "\nApplications (times opened, entries):")?;
+        let mut applications: Vec<_> = self.applications.iter().collect();
+        applications.sort_by_key(|(_, (count, _))| Reverse(*count));
+        let width = number_width(applications.iter().map(|(_, (count, _))| *count));
+        for (name, (count, entries)) in applications {
+            writeln!(out, "  {count:>width$}  {entries:>width$}  {name}")?;
+        }
+
+        write_counts(
+            out,
+            "Schemes",
+            self.schemes.iter().map(|(k, v)| (k.as_str(), *v)),
+        )?;
+        write_counts(
+            out,
+            "Top-level directories",
+            self.directories.iter().map(|(k, v)| (k.as_str(), *v)),
+        )?;
+        write_counts(
+            out,
+            "MIME types",
+            self.mime_types
+                .iter()
+                .map(|(k, v)| (k.as_deref().unwrap_or("(none)"), *v)),
+        )?;
+
+        writeln!(out, "\nLast visited:")?;
+        let labels = AGES.iter().map(|(label, _)| *label).chain(["never"]);
+        let width = number_width(self.ages.iter());
+        for (label, count) in labels.zip(self.ages) {
+            writeln!(out, "  {count:>width$}  {label}")?;
+        }
+        Ok(())
+    }
+}
+
+/// The width of the widest of the numbers.
+fn number_width(numbers: impl Iterator<Item = impl ToString>) -> usize {
+    numbers
+        .map(|number| number.to_string().len())
+        .max()
+        .unwrap_or_default()
+}
+
+/// Print a section of counts, most frequent first.
+fn write_counts<'a>(
+    out: &mut impl Write,
+    title: &str,
+    counts: impl Iterator<Item = (&'a str, usize)>,
+) -> io::Result<()> {
+    writeln!(out, "\n{title}:")?;
+    let mut counts: Vec<_> = counts.collect();
+    counts.sort_by_key(|(_, count)| Reverse(*count));
+    let width = number_width(counts.iter().map(|(_, count)| *count));
+    for (name, count) in counts {
+        writeln!(out, "  {count:>width$}  {name}")?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bookmark;
+    use pretty_assertions::assert_eq;
+
+    const INPUT: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xbel version="1.0"
+      xmlns:bookmark="http://www.freedesktop.org/standards/desktop-bookmarks"
+      xmlns:mime="http://www.freedesktop.org/standards/shared-mime-info"
+>
+  <bookmark href="file:///home/me/A-File.txt" added="2020-09-24T20:00:00Z" modified="2020-09-25T20:00:00Z" visited="2020-09-30T12:00:00Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="text/plain"/>
+        <bookmark:applications>
+          <bookmark:application name="gedit" exec="&apos;gedit %u&apos;" modified="2020-09-25T20:00:00Z" count="12"/>
+          <bookmark:application name="org.gnome.TextEditor" exec="&apos;gnome-text-editor %U&apos;" modified="2020-09-25T20:00:00Z"/>
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>
+  <bookmark href="file:///home/me/A-Video.mp4" added="2020-09-24T20:00:00Z" modified="2020-09-24T20:00:00Z" visited="2020-09-20T20:00:00Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="video/mp4"/>
+        <bookmark:applications>
+          <bookmark:application name="vlc" exec="&apos;vlc %u&apos;" modified="2020-09-24T20:00:00Z" count="3"/>
+          <bookmark:application name="gedit" exec="&apos;gedit %u&apos;" modified="2020-09-25T20:00:00Z" count="1"/>
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>
+  <bookmark href="file:///tmp/B-File.txt" added="2019-09-24T20:00:00Z" modified="2019-09-24T20:00:00Z" visited="2019-09-24T20:00:00Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <mime:mime-type type="text/plain"/>
+      </metadata>
+    </info>
+  </bookmark>
+  <bookmark href="sftp://me@example.com/tmp/C-File.txt" added="2020-09-24T20:00:00Z" modified="2020-09-24T20:00:00Z">
+  </bookmark>
+</xbel>
+"#;
+
+    fn stats() -> Stats {
+        let bookmarks = bookmark::read_all(INPUT.as_bytes()).unwrap();
+        Stats::new(&bookmarks, "2020-10-01T00:00:00Z".parse().unwrap())
+    }
+
+    #[test]
+    fn counts() {
+        let stats = stats();
+        assert_eq!(4, stats.entries);
+        assert_eq!(
+            BTreeMap::from([
+                (String::from("gedit"), (13, 2)),
+                (String::from("org.gnome.TextEditor"), (1, 1)),
+                (String::from("vlc"), (3, 1)),
+            ]),
+            stats.applications
+        );
+        assert_eq!(
+            BTreeMap::from([(String::from("file"), 3), (String::from("sftp"), 1)]),
+            stats.schemes
+        );
+        assert_eq!(
+            BTreeMap::from([(String::from("/home"), 2), (String::from("/tmp"), 1)]),
+            stats.directories
+        );
+        assert_eq!(
+            BTreeMap::from([
+                (None, 1),
+                (Some(String::from("text/plain")), 2),
+                (Some(String::from("video/mp4")), 1),
+            ]),
+            stats.mime_types
+        );
+        assert_eq!([1, 0, 1, 0, 1, 1], stats.ages);
+    }
+
+    #[test]
+    fn write() {
+        let expected = "\
+4 entries
+
+Applications (times opened, entries):
+  13   2  gedit
+   3   1  vlc
+   1   1  org.gnome.TextEditor
+
+Schemes:
+  3  file
+  1  sftp
+
+Top-level directories:
+  2  /home
+  1  /tmp
+
+MIME types:
+  2  text/plain
+  1  (none)
+  1  video/mp4
+
+Last visited:
+  1  within the last day
+  0  within the last week
+  1  within the last 30 days
+  0  within the last year
+  1  longer ago
+  1  never
+";
+        let mut output = Vec::new();
+        stats().write(&mut output).unwrap();
+        assert_eq!(expected, String::from_utf8(output).unwrap());
+    }
+
+    #[test]
+    fn top_level_directories() {
+        assert_eq!("/home", top_level_directory("/home/me/A-File.txt"));
+        assert_eq!("/A-File.txt", top_level_directory("/A-File.txt"));
+        assert_eq!("/", top_level_directory("/"));
+    }
+}